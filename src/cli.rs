@@ -4,12 +4,20 @@
 //! for the context-generator tool. It handles both the main scanning command
 //! and the list-exclusions subcommand.
 
+use crate::clipboard;
+use crate::commands;
+use crate::config::{self, Config};
 use crate::filter::{
-    print_category_exclusions, print_exclusions, print_patterns_only, validate_category_ids, Filter,
+    load_gitignore_rules, print_category_exclusions, print_exclusions, print_patterns_only,
+    validate_category_ids, Filter,
 };
-use crate::scanner::Scanner;
-use clap::{Parser, Subcommand};
-use std::io;
+use crate::output::{self, DEFAULT_FORMAT};
+use crate::plugin::Plugin;
+use crate::scanner::{ProgressReport, ScanStage, Scanner};
+use crate::tokens::{self, TokenCounter};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::io::{self, Write};
 
 /// Main CLI structure defining all command-line arguments and options.
 ///
@@ -40,10 +48,17 @@ pub struct Cli {
     /// Directory to scan (defaults to current directory)
     pub directory: Option<String>,
 
-    /// Exclude files/folders matching these patterns (supports wildcards)
+    /// Exclude files/folders matching these patterns. Defaults to glob syntax; prefix
+    /// with `rootglob:`, `path:`, or `re:` to select root-anchored glob, exact path, or
+    /// raw regex matching instead
     #[arg(long, value_name = "PATTERN")]
     pub exclude: Vec<String>,
 
+    /// Only keep files matching at least one of these patterns (same syntax as
+    /// `--exclude`); exclusion patterns still subtract from this allowlist
+    #[arg(long, visible_alias = "only", value_name = "PATTERN")]
+    pub include: Vec<String>,
+
     /// Disable default exclusion categories by ID (use list-exclusions to see IDs)
     #[arg(long = "disable-category", value_name = "ID")]
     pub disable_category: Vec<String>,
@@ -56,14 +71,121 @@ pub struct Cli {
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Show the files that would be included as a directory tree, without their content
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Output format to serialize scanned context as (plain, markdown, xml, json).
+    /// Defaults to the `.contextgen.toml` config value, or "plain" if unset.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = clap::builder::PossibleValuesParser::new(output::available_formats())
+    )]
+    pub format: Option<String>,
+
+    /// Skip loading `.contextgen.toml`/XDG config files and use built-in defaults only
+    #[arg(long)]
+    pub no_config: bool,
+
+    /// Load the project config from this path instead of discovering
+    /// `.contextgen.toml` by walking up from the current directory
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Maximum file size in bytes to include; larger files are skipped like binaries
+    #[arg(long, value_name = "BYTES")]
+    pub max_size: Option<u64>,
+
+    /// Don't honor `.gitignore`/`.ignore` files, `.git/info/exclude`, or the global
+    /// git excludes file
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Cap total output size to roughly this many tokens, dropping files to fit
+    #[arg(long, value_name = "N", default_value_t = tokens::DEFAULT_TOKEN_LIMIT)]
+    pub max_tokens: usize,
+
+    /// Disable the token budget entirely and include every surviving file regardless
+    /// of size
+    #[arg(long)]
+    pub no_token_limit: bool,
+
+    /// Glob pattern for files to keep first when `--max-tokens` forces omissions
+    /// (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    pub priority: Vec<String>,
+
+    /// Cap the rendered output to roughly this many bytes, truncating with a marker
+    /// if exceeded. Independent of `--max-tokens`: both are applied if set
+    #[arg(long, value_name = "BYTES")]
+    pub max_bytes: Option<u64>,
+
+    /// What to do with the first file that doesn't fit the remaining `--max-tokens`
+    /// budget: drop it like every other overflowing file ("skip", the default), or
+    /// keep it truncated to the remaining budget with a "[...truncated...]" marker
+    /// ("truncate")
+    #[arg(long, value_name = "STRATEGY", default_value = "skip")]
+    pub on_overflow: String,
+
+    /// Run a shell command and include its captured stdout as a synthetic context
+    /// block, e.g. `--command "git status"` (repeatable)
+    #[arg(long = "command", short = 'c', value_name = "CMD")]
+    pub commands: Vec<String>,
+
+    /// Also capture stderr from `--command` invocations, appended after stdout
+    #[arg(long)]
+    pub command_stderr: bool,
+
+    /// Place `--command` output before scanned files instead of after
+    #[arg(long)]
+    pub commands_first: bool,
+
+    /// Override the category/`--exclude`/`--include`/gitignore decision for files
+    /// matching this pattern (same syntax as `--exclude`); prefix with `!` to force a
+    /// file in instead of out. Repeatable; later `--glob` patterns override earlier
+    /// ones
+    #[arg(long = "glob", short = 'g', value_name = "PATTERN")]
+    pub glob: Vec<String>,
+
+    /// Copy the assembled context to the system clipboard instead of printing it to
+    /// stdout; reports the final character/token count to stderr
+    #[arg(long)]
+    pub clipboard: bool,
+
+    /// Number of worker threads to read file contents with (0 = use all available
+    /// parallelism)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub threads: usize,
+
+    /// Path to an external filter/formatter plugin executable (repeatable); see the
+    /// `plugin` module docs for the stdio protocol it must speak
+    #[arg(long, value_name = "PATH")]
+    pub plugin: Vec<String>,
+
+    /// Follow symbolic links while scanning instead of leaving them unvisited.
+    /// Loops and broken links are reported as excluded entries in `--dry-run`
+    /// rather than aborting the scan
+    #[arg(long)]
+    pub follow_symlinks: bool,
+
+    /// Collapse byte-identical included files down to a single copy, replacing
+    /// later duplicates' content with a short stub pointing at the first copy
+    #[arg(long)]
+    pub dedup: bool,
+
+    /// Print a live status line to stderr reporting walk/read progress as the scan runs
+    #[arg(long)]
+    pub progress: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
 /// Available subcommands for the CLI.
 ///
-/// Currently supports the `list-exclusions` subcommand for exploring
-/// available exclusion categories and patterns.
+/// Supports `list-exclusions` for exploring available exclusion categories and
+/// patterns, and `completions` for generating shell completion scripts.
 #[derive(Subcommand)]
 pub enum Commands {
     /// List all default exclusions organized by category
@@ -76,6 +198,12 @@ pub enum Commands {
         #[arg(long)]
         patterns_only: bool,
     },
+
+    /// Generate a shell completion script and print it to stdout
+    Completions {
+        /// Shell to generate completions for (bash, zsh, fish, powershell, elvish)
+        shell: Shell,
+    },
 }
 
 /// Main entry point for CLI execution.
@@ -109,6 +237,9 @@ pub fn run_cli() -> anyhow::Result<()> {
         }) => {
             handle_list_exclusions(category.as_deref(), *patterns_only)?;
         }
+        Some(Commands::Completions { shell }) => {
+            handle_completions(*shell);
+        }
         None => {
             handle_main_command(&cli)?;
         }
@@ -117,6 +248,27 @@ pub fn run_cli() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Handles the `completions` subcommand.
+///
+/// Generates the completion script for `shell` from the same [`Cli`] definition that
+/// drives argument parsing, so flags can never drift out of sync with what gets
+/// completed, and prints it to stdout.
+///
+/// # Arguments
+///
+/// * `shell` - Shell to generate a completion script for
+///
+/// # Examples
+///
+/// ```bash
+/// context-generator completions zsh > _context-generator
+/// ```
+fn handle_completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+}
+
 /// Handles the `list-exclusions` subcommand.
 ///
 /// This function processes the list-exclusions subcommand with its various options:
@@ -173,9 +325,26 @@ fn handle_list_exclusions(category: Option<&str>, patterns_only: bool) -> anyhow
 /// Handles the main scanning command.
 ///
 /// This function processes the main context generation command. It:
-/// 1. Validates any disabled category IDs
-/// 2. Creates an appropriate filter based on options
-/// 3. Creates a scanner and executes either scanning or dry-run mode
+/// 1. Loads the layered `.contextgen.toml`/XDG config (unless `--no-config` is set,
+///    or from `--config <PATH>` directly if given); its `directory`, `no_defaults`,
+///    and `disable_category` values fill in whatever the CLI flags leave unset
+/// 2. Validates the requested `--format` and any disabled category IDs
+/// 3. Creates an appropriate filter, applying an `--include` allowlist if given
+///    (which takes priority over `--no-defaults`), layering in `.gitignore`/
+///    `.ignore` rules, `.git/info/exclude`, and the global git excludes file (unless
+///    `--no-ignore` is set), including any `!pattern` whitelist rules, and finally
+///    layering in any `--glob` override patterns
+/// 4. Creates a scanner, applying `--max-size`, `--threads`, `--follow-symlinks`,
+///    `--dedup`, any `--plugin` processes, and a `--progress` status-line callback if
+///    given, and executes dry-run mode (reporting per-file token estimates against
+///    the `--max-tokens` budget), tree-preview mode (paths only), or scans (reading
+///    surviving files' contents in parallel, offering each to the `--plugin` chain for
+///    filtering and formatting), merges in any `--command` output blocks (ordered via
+///    `--commands-first`), trims the result to the `--max-tokens` budget (on by
+///    default; disable with `--no-token-limit`; `--on-overflow` controls whether the
+///    first oversized file is skipped or truncated), caps the rendered output to
+///    `--max-bytes` if given, and either prints it to stdout or, if `--clipboard` is
+///    set, copies it to the system clipboard instead
 ///
 /// # Arguments
 ///
@@ -198,14 +367,74 @@ fn handle_list_exclusions(category: Option<&str>, patterns_only: bool) -> anyhow
 /// # With options
 /// context-generator --dry-run --exclude "*.backup" src/
 /// ```
+/// `--progress` callback: renders a one-line, carriage-return-overwritten status line
+/// to stderr so it doesn't interleave with the scanned output on stdout.
+fn print_progress(report: ProgressReport) {
+    match report.stage {
+        ScanStage::Walking => eprint!(
+            "\rWalking... {} entries discovered, {} processed",
+            report.entries_discovered, report.entries_processed
+        ),
+        ScanStage::Reading => eprint!(
+            "\rReading... {} files read, {} bytes",
+            report.entries_processed, report.bytes_read
+        ),
+    }
+    let _ = io::stderr().flush();
+}
+
 fn handle_main_command(cli: &Cli) -> anyhow::Result<()> {
-    let directory = cli.directory.as_deref().unwrap_or(".");
-    let exclude_patterns = cli.exclude.clone();
-    let disable_categories: Vec<String> = cli
+    let config = if cli.no_config {
+        Config::default()
+    } else {
+        let cwd = std::env::current_dir()
+            .map_err(|e| anyhow::anyhow!("Error getting current directory: {e}"))?;
+        config::load(&cwd, cli.config.as_deref().map(std::path::Path::new))?
+    };
+
+    let format = cli
+        .format
+        .clone()
+        .or(config.format.clone())
+        .unwrap_or_else(|| DEFAULT_FORMAT.to_string());
+
+    if output::get_generator(&format).is_none() {
+        return Err(anyhow::anyhow!(
+            "Unknown output format '{}'. Available formats: {}",
+            format,
+            output::available_formats().join(", ")
+        ));
+    }
+
+    let overflow_strategy = match cli.on_overflow.as_str() {
+        "skip" => tokens::OverflowStrategy::Skip,
+        "truncate" => tokens::OverflowStrategy::Truncate,
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown --on-overflow strategy '{other}'. Use 'skip' or 'truncate'"
+            ))
+        }
+    };
+
+    let directory = cli
+        .directory
+        .as_deref()
+        .or(config.directory.as_deref())
+        .unwrap_or(".");
+
+    let respect_gitignore = !cli.no_ignore && config.respect_gitignore.unwrap_or(true);
+
+    let no_defaults = cli.no_defaults || config.no_defaults.unwrap_or(false);
+
+    let mut exclude_patterns = cli.exclude.clone();
+    exclude_patterns.extend(config.exclude.clone());
+
+    let mut disable_categories: Vec<String> = cli
         .disable_category
         .iter()
         .flat_map(|s| s.split(',').map(|s| s.trim().to_string()))
         .collect();
+    disable_categories.extend(config.disable_category.clone());
 
     // Validate disabled category IDs
     if !disable_categories.is_empty() {
@@ -218,22 +447,131 @@ fn handle_main_command(cli: &Cli) -> anyhow::Result<()> {
         }
     }
 
-    // Create the appropriate filter
-    let filter = if cli.no_defaults {
+    // Create the appropriate filter. `--include` takes priority over `--no-defaults`
+    // since an allowlist already narrows the scan to the relevant subset.
+    let mut filter = if !cli.include.is_empty() {
+        Filter::with_includes(cli.include.clone(), exclude_patterns, &disable_categories)?
+    } else if no_defaults {
         Filter::new(exclude_patterns)?
     } else {
         Filter::new_with_defaults(exclude_patterns, &disable_categories)?
     };
 
+    if respect_gitignore {
+        filter = filter.with_gitignore_rules(load_gitignore_rules(std::path::Path::new(directory)));
+    }
+
+    if !cli.glob.is_empty() {
+        filter = filter.with_overrides(cli.glob.clone())?;
+    }
+
     // Create scanner and run
-    let scanner = Scanner::new(filter);
+    let mut scanner = Scanner::new(filter);
+    if let Some(max_size) = cli.max_size.or(config.max_size) {
+        scanner = scanner.with_max_size(max_size);
+    }
+    if cli.threads > 0 {
+        scanner = scanner.with_threads(cli.threads);
+    }
+    if cli.follow_symlinks {
+        scanner = scanner.with_follow_symlinks(true);
+    }
+    if cli.dedup {
+        scanner = scanner.with_dedup(true);
+    }
+    if !cli.plugin.is_empty() {
+        let mut plugins = Vec::new();
+        for path in &cli.plugin {
+            match Plugin::spawn(path) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => eprintln!("Warning: could not start plugin {path:?}: {e}"),
+            }
+        }
+        scanner = scanner.with_plugins(plugins);
+    }
+    if cli.progress {
+        scanner = scanner.with_progress(print_progress);
+    }
     let mut stdout = io::stdout();
 
     if cli.dry_run {
-        scanner.dry_run(directory, &mut stdout)?;
+        scanner.dry_run_as(directory, &format, cli.max_tokens, &mut stdout)?;
+    } else if cli.tree {
+        scanner.tree(directory, &mut stdout)?;
     } else {
-        scanner.scan(directory, &mut stdout)?;
+        let mut files = scanner.collect(directory)?;
+        let command_files = commands::run_commands(&cli.commands, cli.command_stderr)?;
+
+        if cli.commands_first {
+            let mut merged = command_files;
+            merged.extend(files);
+            files = merged;
+        } else {
+            files.extend(command_files);
+        }
+
+        let generator =
+            output::get_generator(&format).expect("format was already validated above");
+
+        let mut rendered = Vec::new();
+
+        if cli.no_token_limit {
+            generator.render(&files, &mut rendered)?;
+        } else {
+            let budget = tokens::fit_to_budget(
+                files,
+                cli.max_tokens,
+                &cli.priority,
+                &tokens::BpeTokenCounter::new(),
+                overflow_strategy,
+            );
+            generator.render(&budget.included, &mut rendered)?;
+            tokens::write_summary(&budget, cli.max_tokens, &mut rendered)?;
+        }
+
+        if let Some(max_bytes) = cli.max_bytes {
+            let max_bytes = max_bytes as usize;
+            if rendered.len() > max_bytes {
+                rendered.truncate(max_bytes);
+                rendered.extend_from_slice(
+                    format!("\n[...truncated: output exceeded --max-bytes {max_bytes}...]\n")
+                        .as_bytes(),
+                );
+            }
+        }
+
+        if cli.clipboard {
+            let content = String::from_utf8_lossy(&rendered).into_owned();
+            clipboard::copy_to_clipboard(&content)?;
+            eprintln!(
+                "Copied {} characters (~{} tokens) to clipboard",
+                content.chars().count(),
+                tokens::BpeTokenCounter::new().count(&content)
+            );
+        } else {
+            stdout.write_all(&rendered)?;
+        }
+    }
+
+    if cli.progress {
+        eprintln!();
     }
 
     Ok(())
 }
+
+/// `--progress` callback: renders a one-line, carriage-return-overwritten status line
+/// to stderr so it doesn't interleave with the scanned output on stdout.
+fn print_progress(report: ProgressReport) {
+    match report.stage {
+        ScanStage::Walking => eprint!(
+            "\rWalking... {} entries discovered, {} processed",
+            report.entries_discovered, report.entries_processed
+        ),
+        ScanStage::Reading => eprint!(
+            "\rReading... {} files read, {} bytes",
+            report.entries_processed, report.bytes_read
+        ),
+    }
+    let _ = io::stderr().flush();
+}
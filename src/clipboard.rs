@@ -0,0 +1,29 @@
+//! System clipboard output sink.
+//!
+//! Backs the `--clipboard` flag: instead of writing assembled context to stdout, it
+//! goes straight onto the system clipboard so it can be pasted directly into a chat
+//! UI, skipping the usual `| pbcopy` / `| xclip` shell round-trip.
+
+use arboard::Clipboard;
+
+/// Copies `text` to the system clipboard.
+///
+/// # Arguments
+///
+/// * `text` - Content to place on the clipboard, replacing whatever was there before
+///
+/// # Returns
+///
+/// * `Ok(())` - Successfully copied
+/// * `Err(anyhow::Error)` - No clipboard available (e.g. headless Linux with no X11/Wayland
+///   session), or the underlying platform API failed
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<()> {
+    let mut clipboard =
+        Clipboard::new().map_err(|e| anyhow::anyhow!("Error accessing system clipboard: {e}"))?;
+
+    clipboard
+        .set_text(text)
+        .map_err(|e| anyhow::anyhow!("Error copying to clipboard: {e}"))?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Shell command output as a synthetic context source.
+//!
+//! This module runs user-supplied shell commands (`--command`/`-c`) and wraps their
+//! captured output as [`ScannedFile`]s, so live project state (`git status`, `tree`,
+//! `cargo metadata`, ...) can sit alongside file content in the same rendered output
+//! and participate in the same `--max-tokens` budget, without [`Scanner`](crate::scanner::Scanner)
+//! or [`OutputGenerator`](crate::output::OutputGenerator) needing to know the content
+//! didn't come from disk.
+
+use crate::scanner::ScannedFile;
+use std::process::Command;
+
+/// Runs each command in `commands` through the system shell and collects its output
+/// as a [`ScannedFile`], in the same order the commands were given.
+///
+/// Each command is run via `sh -c` (`cmd /C` on Windows) so pipes, globs, and shell
+/// builtins behave the way a user typing the command at a terminal would expect.
+/// Commands are run sequentially and independently: a failing command doesn't stop
+/// the rest, it just surfaces its exit status (and, with `include_stderr`, its stderr)
+/// in its own block. A command that can't be spawned at all is an error that stops
+/// the whole run.
+///
+/// # Arguments
+///
+/// * `commands` - Shell command strings to execute, e.g. `"git status"`
+/// * `include_stderr` - Whether to append captured stderr to the block after stdout
+///
+/// # Returns
+///
+/// One [`ScannedFile`] per command, synthetic `path` of the form `$ <command>` so
+/// output generators render it like a file whose "path" is the command itself.
+pub fn run_commands(commands: &[String], include_stderr: bool) -> anyhow::Result<Vec<ScannedFile>> {
+    commands.iter().map(|command| run_command(command, include_stderr)).collect()
+}
+
+fn run_command(command: &str, include_stderr: bool) -> anyhow::Result<ScannedFile> {
+    let output = shell_command(command)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Error running command {command:?}: {e}"))?;
+
+    let mut content = String::from_utf8_lossy(&output.stdout).into_owned();
+
+    if include_stderr && !output.stderr.is_empty() {
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&String::from_utf8_lossy(&output.stderr));
+    }
+
+    if !output.status.success() {
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!(
+            "(command exited with status {})\n",
+            output.status
+        ));
+    }
+
+    Ok(ScannedFile {
+        path: format!("$ {command}"),
+        content,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
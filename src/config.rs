@@ -0,0 +1,233 @@
+//! Layered configuration file support.
+//!
+//! Users repeatedly pass the same filter and scan flags on every invocation. This
+//! module loads a project-level `.contextgen.toml` (discovered by walking up from the
+//! working directory) and a user-level default in the XDG config directory, then
+//! merges them into a single [`Config`] that `cli` applies before looking at explicit
+//! command-line flags.
+//!
+//! # Precedence
+//!
+//! `CLI flag > project config > user config > built-in default`, with a `--no-config`
+//! flag to skip this module entirely and fall back to built-in defaults.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use context_generator::config::Config;
+//!
+//! let project = Config {
+//!     exclude: vec!["*.generated.rs".to_string()],
+//!     ..Config::default()
+//! };
+//! let user = Config {
+//!     format: Some("markdown".to_string()),
+//!     ..Config::default()
+//! };
+//!
+//! // Project config wins on overlapping scalar fields; list fields accumulate.
+//! let merged = project.merge(user);
+//! assert_eq!(merged.format.as_deref(), Some("markdown"));
+//! ```
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Project-level config file name, discovered by walking up from the working directory.
+pub const PROJECT_FILE_NAME: &str = ".contextgen.toml";
+
+/// Defaults for filter/scanner options, loaded from a `.contextgen.toml` file.
+///
+/// Every field is optional so that a config file only needs to set the options a
+/// user actually wants to override; anything left unset falls through to the next
+/// layer in the precedence chain.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct Config {
+    /// Additional exclusion glob patterns, merged with `--exclude` and the built-in
+    /// categories (unless `--no-defaults` is passed).
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Category IDs to disable, merged with `--disable-category`.
+    #[serde(default)]
+    pub disable_category: Vec<String>,
+
+    /// Whether to skip built-in exclusion categories entirely, same as `--no-defaults`.
+    #[serde(default)]
+    pub no_defaults: Option<bool>,
+
+    /// Directory to scan, used when no directory is given on the command line.
+    #[serde(default)]
+    pub directory: Option<String>,
+
+    /// Maximum file size in bytes to include in the generated context.
+    #[serde(default)]
+    pub max_size: Option<u64>,
+
+    /// Whether to honor `.gitignore`/`.ignore` files while scanning.
+    #[serde(default)]
+    pub respect_gitignore: Option<bool>,
+
+    /// Default `--format` identifier to serialize scanned context as.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+impl Config {
+    /// Merges `self` (higher precedence) with `other` (lower precedence).
+    ///
+    /// List fields accumulate, `self`'s entries first. Scalar `Option` fields keep
+    /// `self`'s value if set, otherwise fall back to `other`'s.
+    pub fn merge(mut self, other: Config) -> Config {
+        self.exclude.extend(other.exclude);
+        self.disable_category.extend(other.disable_category);
+        self.no_defaults = self.no_defaults.or(other.no_defaults);
+        self.directory = self.directory.or(other.directory);
+        self.max_size = self.max_size.or(other.max_size);
+        self.respect_gitignore = self.respect_gitignore.or(other.respect_gitignore);
+        self.format = self.format.or(other.format);
+        self
+    }
+}
+
+/// Walks up from `start_dir` looking for [`PROJECT_FILE_NAME`], returning the first
+/// one found.
+fn discover_project_config(start_dir: &Path) -> Option<PathBuf> {
+    let mut dir = Some(start_dir.to_path_buf());
+
+    while let Some(current) = dir {
+        let candidate = current.join(PROJECT_FILE_NAME);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        dir = current.parent().map(Path::to_path_buf);
+    }
+
+    None
+}
+
+/// Returns the user-level config path in the XDG config directory
+/// (`$XDG_CONFIG_HOME/context-generator/config.toml`, or its platform equivalent).
+fn user_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("context-generator").join("config.toml"))
+}
+
+/// Parses a `.contextgen.toml`-style file at `path`.
+fn load_from_path(path: &Path) -> anyhow::Result<Config> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Error reading config file {:?}: {}", path, e))?;
+
+    toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Error parsing config file {:?}: {}", path, e))
+}
+
+/// Loads and merges the project and user config layers, starting the project-config
+/// walk from `start_dir` (typically the current working directory).
+///
+/// Returns [`Config::default`] if neither file exists.
+///
+/// # Arguments
+///
+/// * `start_dir` - Directory to start walking up from when looking for
+///   [`PROJECT_FILE_NAME`]
+/// * `explicit_path` - When set (`--config <PATH>`), read the project layer from this
+///   path instead of discovering it by walking up from `start_dir`; the file must exist
+///
+/// # Returns
+///
+/// * `Ok(Config)` - Merged configuration (project layer takes precedence over user)
+/// * `Err(anyhow::Error)` - A config file exists but could not be read or parsed, or
+///   `explicit_path` was given but doesn't exist
+pub fn load(start_dir: &Path, explicit_path: Option<&Path>) -> anyhow::Result<Config> {
+    let project = match explicit_path {
+        Some(path) => load_from_path(path)?,
+        None => match discover_project_config(start_dir) {
+            Some(path) => load_from_path(&path)?,
+            None => Config::default(),
+        },
+    };
+
+    let user = match user_config_path() {
+        Some(path) if path.is_file() => load_from_path(&path)?,
+        _ => Config::default(),
+    };
+
+    Ok(project.merge(user))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_scalar_fields_prefer_self() {
+        let project = Config {
+            format: Some("xml".to_string()),
+            max_size: Some(1024),
+            ..Config::default()
+        };
+        let user = Config {
+            format: Some("markdown".to_string()),
+            max_size: Some(2048),
+            respect_gitignore: Some(false),
+            ..Config::default()
+        };
+
+        let merged = project.merge(user);
+
+        // project's values win where both set...
+        assert_eq!(merged.format.as_deref(), Some("xml"));
+        assert_eq!(merged.max_size, Some(1024));
+        // ...but user's value fills in a field project left unset.
+        assert_eq!(merged.respect_gitignore, Some(false));
+    }
+
+    #[test]
+    fn test_merge_list_fields_accumulate_self_first() {
+        let project = Config {
+            exclude: vec!["*.generated.rs".to_string()],
+            disable_category: vec!["go".to_string()],
+            ..Config::default()
+        };
+        let user = Config {
+            exclude: vec!["*.bak".to_string()],
+            disable_category: vec!["python".to_string()],
+            ..Config::default()
+        };
+
+        let merged = project.merge(user);
+
+        assert_eq!(merged.exclude, vec!["*.generated.rs", "*.bak"]);
+        assert_eq!(merged.disable_category, vec!["go", "python"]);
+    }
+
+    #[test]
+    fn test_merge_with_default_is_identity() {
+        let project = Config {
+            format: Some("json".to_string()),
+            exclude: vec!["*.log".to_string()],
+            ..Config::default()
+        };
+
+        let merged = project.clone().merge(Config::default());
+
+        assert_eq!(merged, project);
+    }
+
+    #[test]
+    fn test_discover_project_config_walks_up_ancestors() {
+        let root = std::env::temp_dir().join(format!(
+            "context-generator-test-config-discover-{}",
+            std::process::id()
+        ));
+        let nested = root.join("a").join("b");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(PROJECT_FILE_NAME), "").unwrap();
+
+        let found = discover_project_config(&nested);
+        assert_eq!(found, Some(root.join(PROJECT_FILE_NAME)));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
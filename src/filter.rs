@@ -19,10 +19,15 @@
 //! let categories = get_exclusion_categories();
 //! ```
 
-use glob::Pattern;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::OnceLock;
+
+/// Names of ignore files consulted by [`load_gitignore_rules`], in the order
+/// their contents are read.
+const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".ignore"];
 
 /// Embedded YAML file containing all exclusion categories and patterns.
 /// This is loaded at compile time using `include_str!` for zero runtime cost.
@@ -66,7 +71,7 @@ struct ExclusionData {
 ///
 /// * `pattern` - The specific glob pattern that matched (e.g., "*.log")
 /// * `category` - The name of the category this pattern belongs to (e.g., "Logs & Temporary")
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ExclusionReason {
     pub pattern: String,
     pub category: String,
@@ -93,26 +98,243 @@ pub struct ExclusionReason {
 /// ).unwrap();
 /// ```
 pub struct Filter {
-    /// Compiled glob patterns for efficient matching
-    patterns: Vec<Pattern>,
+    /// Compiled patterns for efficient matching
+    patterns: Vec<CompiledPattern>,
     /// Maps pattern strings to their category names for reporting
     pattern_to_category: HashMap<String, String>,
+    /// Per-directory `.gitignore`/`.ignore` matchers, consulted deepest-first (see
+    /// [`evaluate_gitignore_matchers`]) after `patterns` finds no match.
+    gitignore_matchers: Vec<GitignoreMatcher>,
+    /// Whether an include/allowlist restricts the files this filter keeps.
+    include_rule: FilterRule,
+    /// Ordered `--glob`/`-g` override rules, evaluated last (see
+    /// [`evaluate_override_rules`]) to force specific files in or out of the final set
+    /// regardless of what category/gitignore matching decided.
+    overrides: Vec<OverrideRule>,
+}
+
+/// Whether a [`Filter`] restricts its output to an allowlist of include patterns.
+///
+/// Checked before the ordinary exclusion patterns: a file failing the allowlist is
+/// excluded outright (category "Not included"), while a file passing it still has to
+/// clear `patterns` and `gitignore_matchers` as usual, so exclusions continue to subtract
+/// from the allowed set rather than being bypassed by it.
+enum FilterRule {
+    /// No allowlist — everything not explicitly excluded is kept.
+    All,
+    /// Only files matching at least one of these patterns are kept.
+    Just(Vec<CompiledPattern>),
+}
+
+/// Which dialect a pattern string selects, via an optional prefix.
+///
+/// Defaults to [`PatternSyntax::Glob`] when a pattern carries no recognized prefix, so
+/// every pre-existing plain glob pattern (categories, `--exclude`) keeps working
+/// unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternSyntax {
+    /// `glob:` (or unprefixed) — shell-style glob, matched against either the
+    /// basename or the path relative to the scan root.
+    Glob,
+    /// `rootglob:` — shell-style glob anchored to the scan root, matched only
+    /// against the relative path.
+    RootGlob,
+    /// `path:` — a literal path relative to the scan root, matched exactly.
+    Path,
+    /// `re:` — a raw regular expression, matched against the relative path.
+    Regex,
+}
+
+/// Splits a pattern string into its [`PatternSyntax`] and the remaining pattern body,
+/// based on a recognized `glob:`/`rootglob:`/`path:`/`re:` prefix.
+fn split_pattern_syntax(pattern: &str) -> (PatternSyntax, &str) {
+    if let Some(rest) = pattern.strip_prefix("re:") {
+        (PatternSyntax::Regex, rest)
+    } else if let Some(rest) = pattern.strip_prefix("rootglob:") {
+        (PatternSyntax::RootGlob, rest)
+    } else if let Some(rest) = pattern.strip_prefix("path:") {
+        (PatternSyntax::Path, rest)
+    } else if let Some(rest) = pattern.strip_prefix("glob:") {
+        (PatternSyntax::Glob, rest)
+    } else {
+        (PatternSyntax::Glob, pattern)
+    }
+}
+
+/// Per-byte regex-escape table, built once and indexed directly rather than matching
+/// against a metacharacter set on every character — patterns are compiled once but
+/// matched against every scanned path, so the translator itself stays off the hot path.
+fn regex_escape_table() -> &'static [String; 256] {
+    static TABLE: OnceLock<[String; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        const METACHARS: &[u8] = b".+()|^$\\{}";
+        std::array::from_fn(|b| {
+            let byte = b as u8;
+            if METACHARS.contains(&byte) {
+                format!("\\{}", byte as char)
+            } else {
+                (byte as char).to_string()
+            }
+        })
+    })
+}
+
+/// Escapes the literal (non-glob-syntax) characters of `pattern`, leaving `*`, `?`,
+/// `[`, `]`, and `/` untouched so [`glob_to_re`] can give them glob semantics.
+fn escape_glob_literals(pattern: &str) -> String {
+    let table = regex_escape_table();
+    let mut out = String::new();
+
+    for c in pattern.chars() {
+        match c {
+            '*' | '?' | '/' | '[' | ']' => out.push(c),
+            c if (c as u32) < 256 => out.push_str(&table[c as usize]),
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    out
+}
+
+/// Translates a shell-style glob into an equivalent regex pattern.
+///
+/// Metacharacters are escaped first, then glob syntax is applied as a series of
+/// ordered string replacements: `*/` becomes an optional "any directories" prefix,
+/// `**` becomes an unrestricted span, a bare `*` is restricted to a single path
+/// segment, `?` matches a single character, and `[...]` character classes pass
+/// through untouched. The result is anchored with a trailing `(?:/|$)` so a directory
+/// pattern (e.g. `node_modules`) also matches everything beneath it once the caller
+/// anchors the start.
+fn glob_to_re(pattern: &str) -> String {
+    let escaped = escape_glob_literals(pattern);
+    // `*/` and `**` go through placeholder characters first, so the later `*` and `?`
+    // steps can't rewrite the regex syntax those two replacements emit.
+    let step1 = escaped.replace("*/", "\u{0}");
+    let step2 = step1.replace("**", "\u{1}");
+    let step3 = step2.replace('*', "[^/]*");
+    let step4 = step3.replace('?', ".");
+    let step5 = step4.replace('\u{0}', "(?:.*/)?").replace('\u{1}', ".*");
+    format!("{step5}(?:/|$)")
+}
+
+/// How a [`CompiledPattern`] is matched against a candidate path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchMode {
+    /// Match against the basename, or against the relative path anchored at its start.
+    NameOrRelPath,
+    /// Match only against the relative path, anchored at its start.
+    RelPathOnly,
+}
+
+/// A single pattern compiled to a `regex::Regex`, replacing the old one-dialect
+/// `glob::Pattern` matcher so `glob:`/`rootglob:`/`path:`/`re:` patterns can all be
+/// matched through the same engine.
+struct CompiledPattern {
+    /// The original pattern string, including any syntax prefix, used to look up the
+    /// category in [`Filter::pattern_to_category`] and for reporting.
+    original: String,
+    regex: Regex,
+    mode: MatchMode,
+    /// Whether this pattern is a plain literal name (`node_modules`, `.git`, `target`)
+    /// rather than a wildcard/extension pattern (`*.log`). Only directory-style
+    /// patterns are consulted by [`Filter::should_prune_dir`], since a pattern like
+    /// `*.log` targets files by extension and coincidentally sharing a name with a
+    /// directory shouldn't prune an entire subtree unseen.
+    is_dir_style: bool,
+}
+
+impl CompiledPattern {
+    fn matches(&self, name: &str, rel_path: &str) -> bool {
+        match self.mode {
+            MatchMode::NameOrRelPath => self.regex.is_match(name) || self.regex.is_match(rel_path),
+            MatchMode::RelPathOnly => self.regex.is_match(rel_path),
+        }
+    }
+}
+
+/// Compiles a pattern string (optionally carrying a `glob:`/`rootglob:`/`path:`/`re:`
+/// prefix) into a [`CompiledPattern`].
+fn compile_pattern(pattern_str: &str) -> Result<CompiledPattern, regex::Error> {
+    let (syntax, rest) = split_pattern_syntax(pattern_str);
+
+    let (regex_source, mode) = match syntax {
+        PatternSyntax::Regex => (rest.to_string(), MatchMode::RelPathOnly),
+        PatternSyntax::Path => (format!("^{}$", regex::escape(rest)), MatchMode::RelPathOnly),
+        PatternSyntax::RootGlob => (format!("^{}", glob_to_re(rest)), MatchMode::RelPathOnly),
+        PatternSyntax::Glob => (format!("^{}", glob_to_re(rest)), MatchMode::NameOrRelPath),
+    };
+
+    let is_dir_style = match syntax {
+        PatternSyntax::Regex => false,
+        PatternSyntax::Glob | PatternSyntax::RootGlob | PatternSyntax::Path => {
+            !rest.contains(['*', '?', '['])
+        }
+    };
+
+    Ok(CompiledPattern {
+        original: pattern_str.to_string(),
+        regex: Regex::new(&regex_source)?,
+        mode,
+        is_dir_style,
+    })
+}
+
+/// A single `--glob`/`-g` override rule, evaluated after category/custom-pattern and
+/// `.gitignore` matching to force a specific file in or out of the final set. Supports
+/// the same `glob:`/`rootglob:`/`path:`/`re:` syntax prefixes as `--exclude`/`--include`
+/// via [`compile_pattern`], with an optional leading `!` to mark it as a re-include
+/// rather than an exclude.
+struct OverrideRule {
+    /// The original pattern string, including any leading `!`, used for reporting.
+    original: String,
+    pattern: CompiledPattern,
+    is_negation: bool,
+}
+
+/// Compiles a single `--glob` pattern string into an [`OverrideRule`], stripping a
+/// leading `!` (if present) before handing the rest to [`compile_pattern`].
+fn compile_override_rule(pattern_str: &str) -> Result<OverrideRule, regex::Error> {
+    let is_negation = pattern_str.starts_with('!');
+    let rest = if is_negation { &pattern_str[1..] } else { pattern_str };
+
+    Ok(OverrideRule {
+        original: pattern_str.to_string(),
+        pattern: compile_pattern(rest)?,
+        is_negation,
+    })
+}
+
+/// Evaluates the `--glob` override layer against a candidate file, with last-match-wins
+/// semantics: later patterns override earlier ones, so the last matching rule decides
+/// the outcome outright (whether it's a plain exclude or a `!` re-include).
+///
+/// Returns the rule that made the decision, or `None` if no override pattern matched.
+fn evaluate_override_rules<'a>(
+    rules: &'a [OverrideRule],
+    name: &str,
+    rel_path: &str,
+) -> Option<&'a OverrideRule> {
+    rules.iter().rev().find(|rule| rule.pattern.matches(name, rel_path))
 }
 
 impl Filter {
     /// Creates a new filter with only the specified custom patterns.
     ///
     /// This creates a minimal filter that excludes only files matching the provided
-    /// glob patterns. No default exclusions are applied.
+    /// patterns. No default exclusions are applied.
+    ///
+    /// Each pattern defaults to `glob:` syntax, but may opt into `rootglob:` (glob
+    /// anchored to the scan root), `path:` (exact relative path), or `re:` (raw
+    /// regex) by prefixing it accordingly — see [`compile_pattern`].
     ///
     /// # Arguments
     ///
-    /// * `patterns` - Vector of glob pattern strings (e.g., `["*.tmp", "build/*"]`)
+    /// * `patterns` - Vector of pattern strings (e.g., `["*.tmp", "build/*", "re:^src/.*_test\\.rs$"]`)
     ///
     /// # Returns
     ///
     /// * `Ok(Filter)` - Successfully created filter
-    /// * `Err(glob::PatternError)` - Invalid glob pattern provided
+    /// * `Err(regex::Error)` - Invalid pattern (bad glob syntax or raw regex)
     ///
     /// # Examples
     ///
@@ -125,12 +347,12 @@ impl Filter {
     ///     ".DS_Store".to_string(),
     /// ]).unwrap();
     /// ```
-    pub fn new(patterns: Vec<String>) -> Result<Self, glob::PatternError> {
+    pub fn new(patterns: Vec<String>) -> Result<Self, regex::Error> {
         let mut compiled_patterns = Vec::new();
         let mut pattern_to_category = HashMap::new();
 
         for pattern_str in patterns {
-            let pattern = Pattern::new(&pattern_str)?;
+            let pattern = compile_pattern(&pattern_str)?;
             compiled_patterns.push(pattern);
             pattern_to_category.insert(pattern_str, "Custom".to_string());
         }
@@ -138,6 +360,9 @@ impl Filter {
         Ok(Filter {
             patterns: compiled_patterns,
             pattern_to_category,
+            gitignore_matchers: Vec::new(),
+            include_rule: FilterRule::All,
+            overrides: Vec::new(),
         })
     }
 
@@ -155,7 +380,7 @@ impl Filter {
     /// # Returns
     ///
     /// * `Ok(Filter)` - Successfully created filter
-    /// * `Err(glob::PatternError)` - Invalid glob pattern in defaults or additional patterns
+    /// * `Err(regex::Error)` - Invalid pattern in defaults or additional patterns
     ///
     /// # Examples
     ///
@@ -177,7 +402,7 @@ impl Filter {
     pub fn new_with_defaults(
         additional_patterns: Vec<String>,
         disabled_category_ids: &[String],
-    ) -> Result<Self, glob::PatternError> {
+    ) -> Result<Self, regex::Error> {
         let default_patterns = get_filtered_patterns(disabled_category_ids);
         let mut all_patterns = default_patterns;
         all_patterns.extend(additional_patterns);
@@ -186,7 +411,7 @@ impl Filter {
         let mut pattern_to_category = HashMap::new();
 
         for pattern_str in all_patterns {
-            let pattern = Pattern::new(&pattern_str)?;
+            let pattern = compile_pattern(&pattern_str)?;
             compiled_patterns.push(pattern);
 
             let category = get_category_for_pattern(&pattern_str);
@@ -196,9 +421,115 @@ impl Filter {
         Ok(Filter {
             patterns: compiled_patterns,
             pattern_to_category,
+            gitignore_matchers: Vec::new(),
+            include_rule: FilterRule::All,
+            overrides: Vec::new(),
         })
     }
 
+    /// Creates a new filter restricted to an include/allowlist, on top of default
+    /// exclusions plus additional custom exclude patterns.
+    ///
+    /// A file is kept only if it matches at least one `includes` pattern *and* isn't
+    /// separately excluded — the allowlist narrows the candidate set, exclusions still
+    /// subtract from it. An empty `includes` list behaves exactly like
+    /// [`Filter::new_with_defaults`] (no allowlist applied).
+    ///
+    /// # Arguments
+    ///
+    /// * `includes` - Patterns a file must match at least one of to be kept
+    /// * `excludes` - Extra custom exclude patterns to add beyond defaults
+    /// * `disabled_category_ids` - Category IDs to disable (e.g., `["logs", "vcs"]`)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Filter)` - Successfully created filter
+    /// * `Err(regex::Error)` - Invalid pattern among `includes`, `excludes`, or defaults
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::filter::Filter;
+    ///
+    /// // Only keep Rust sources and Markdown docs
+    /// let filter = Filter::with_includes(
+    ///     vec!["src/**/*.rs".to_string(), "*.md".to_string()],
+    ///     vec![],
+    ///     &[],
+    /// ).unwrap();
+    /// ```
+    pub fn with_includes(
+        includes: Vec<String>,
+        excludes: Vec<String>,
+        disabled_category_ids: &[String],
+    ) -> Result<Self, regex::Error> {
+        let mut filter = Self::new_with_defaults(excludes, disabled_category_ids)?;
+
+        if !includes.is_empty() {
+            let mut include_patterns = Vec::new();
+            for pattern_str in includes {
+                include_patterns.push(compile_pattern(&pattern_str)?);
+            }
+            filter.include_rule = FilterRule::Just(include_patterns);
+        }
+
+        Ok(filter)
+    }
+
+    /// Adds `.gitignore`/`.ignore` matchers to be consulted whenever the flat
+    /// category/custom pattern list finds no match.
+    ///
+    /// Unlike `patterns`, these support negation (`!pattern`) and directory-scoped
+    /// precedence via [`evaluate_gitignore_matchers`], so a nested `.gitignore` can
+    /// re-include a file an ancestor's broad rule excluded. Use
+    /// [`load_gitignore_rules`] to build `rules` from the files under a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::filter::{load_gitignore_rules, Filter};
+    /// use std::path::Path;
+    ///
+    /// let rules = load_gitignore_rules(Path::new("."));
+    /// let filter = Filter::new(vec![])
+    ///     .unwrap()
+    ///     .with_gitignore_rules(rules);
+    /// ```
+    pub fn with_gitignore_rules(mut self, rules: Vec<GitignoreMatcher>) -> Self {
+        self.gitignore_matchers = rules;
+        self
+    }
+
+    /// Adds an ordered `--glob`/`-g` override layer, evaluated after category/custom
+    /// patterns and `.gitignore` rules have made their decision.
+    ///
+    /// Each pattern supports the same `glob:`/`rootglob:`/`path:`/`re:` syntax as
+    /// `--exclude`, plus an optional leading `!` to re-include a file instead of
+    /// excluding it. Later patterns override earlier ones: the last override pattern
+    /// matching a given file decides its fate outright, regardless of what the
+    /// category/gitignore layers decided.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::filter::Filter;
+    ///
+    /// // Force out generated fixtures, but bring back one specific file despite it
+    /// // already being covered by a category pattern.
+    /// let filter = Filter::new_with_defaults(vec![], &[])
+    ///     .unwrap()
+    ///     .with_overrides(vec!["testdata/*.golden".to_string(), "!testdata/keep.golden".to_string()])
+    ///     .unwrap();
+    /// ```
+    pub fn with_overrides(mut self, overrides: Vec<String>) -> Result<Self, regex::Error> {
+        let mut compiled = Vec::new();
+        for pattern_str in overrides {
+            compiled.push(compile_override_rule(&pattern_str)?);
+        }
+        self.overrides = compiled;
+        Ok(self)
+    }
+
     /// Determines if a file should be excluded based on the configured patterns.
     ///
     /// This is a convenience method that returns a simple boolean. For more detailed
@@ -244,7 +575,7 @@ impl Filter {
     ///
     /// * `path` - Path to the file to check
     /// * `base_dir` - Base directory for calculating relative paths
-    /// * `_is_dir` - Whether the path represents a directory (currently unused)
+    /// * `is_dir` - Whether the path represents a directory
     ///
     /// # Returns
     ///
@@ -265,36 +596,190 @@ impl Filter {
     ///     println!("Excluded by pattern '{}' in category '{}'", reason.pattern, reason.category);
     /// }
     /// ```
+    ///
+    /// If `--glob`/`-g` overrides were added via [`Filter::with_overrides`], they get
+    /// the final say: the last override pattern matching the file wins outright, even
+    /// if it contradicts what category/gitignore matching decided above.
     pub fn get_exclusion_reason<P: AsRef<Path>>(
         &self,
         path: P,
         base_dir: P,
-        _is_dir: bool,
+        is_dir: bool,
     ) -> Option<ExclusionReason> {
         let path = path.as_ref();
         let base_dir = base_dir.as_ref();
 
+        let reason = self.base_exclusion_reason(path, base_dir, is_dir);
+
+        if self.overrides.is_empty() {
+            return reason;
+        }
+
+        let rel_path = path.strip_prefix(base_dir).unwrap_or(path);
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return reason;
+        };
+        let rel_path_str = rel_path.to_string_lossy();
+
+        match evaluate_override_rules(&self.overrides, name, &rel_path_str) {
+            Some(rule) if rule.is_negation => None,
+            Some(rule) => Some(ExclusionReason {
+                pattern: rule.original.clone(),
+                category: "Glob Override".to_string(),
+            }),
+            None => reason,
+        }
+    }
+
+    /// Category/custom-pattern/`.gitignore` exclusion decision, before the `--glob`
+    /// override layer in [`Filter::get_exclusion_reason`] gets a say.
+    fn base_exclusion_reason(
+        &self,
+        path: &Path,
+        base_dir: &Path,
+        is_dir: bool,
+    ) -> Option<ExclusionReason> {
         let rel_path = path.strip_prefix(base_dir).unwrap_or(path);
         let name = path.file_name()?.to_str()?;
 
+        let rel_path_str = rel_path.to_string_lossy();
+
+        if let FilterRule::Just(include_patterns) = &self.include_rule {
+            let included = include_patterns
+                .iter()
+                .any(|pattern| pattern.matches(name, &rel_path_str));
+            if !included {
+                return Some(ExclusionReason {
+                    pattern: "<no include pattern matched>".to_string(),
+                    category: "Not included".to_string(),
+                });
+            }
+        }
+
         for pattern in &self.patterns {
-            if pattern.matches(name) || pattern.matches(&rel_path.to_string_lossy()) {
-                let pattern_str = pattern.as_str();
+            if pattern.matches(name, &rel_path_str) {
                 let category = self
                     .pattern_to_category
-                    .get(pattern_str)
+                    .get(&pattern.original)
                     .cloned()
                     .unwrap_or_else(|| "Custom".to_string());
 
                 return Some(ExclusionReason {
-                    pattern: pattern_str.to_string(),
+                    pattern: pattern.original.clone(),
                     category,
                 });
             }
         }
 
+        if let Some(reason) = evaluate_gitignore_matchers(&self.gitignore_matchers, path, is_dir) {
+            return Some(reason);
+        }
+
         None
     }
+
+    /// Returns which `--include` allowlist pattern let `path` through, for `--dry-run`
+    /// reporting alongside [`Filter::get_exclusion_reason`]'s exclusion reasons.
+    ///
+    /// Returns `None` when no allowlist is configured ([`FilterRule::All`]), or when no
+    /// pattern matches — the latter shouldn't happen for a file that already passed
+    /// [`Filter::get_exclusion_reason`], since that method checks the same allowlist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::filter::Filter;
+    /// use std::path::Path;
+    ///
+    /// let filter = Filter::with_includes(vec!["*.rs".to_string()], vec![], &[]).unwrap();
+    /// let base_dir = Path::new("/project");
+    /// let rs_file = Path::new("/project/main.rs");
+    ///
+    /// let reason = filter.include_match_reason(rs_file, base_dir).unwrap();
+    /// assert_eq!(reason.pattern, "*.rs");
+    /// ```
+    pub fn include_match_reason<P: AsRef<Path>>(
+        &self,
+        path: P,
+        base_dir: P,
+    ) -> Option<ExclusionReason> {
+        let path = path.as_ref();
+        let base_dir = base_dir.as_ref();
+
+        let FilterRule::Just(include_patterns) = &self.include_rule else {
+            return None;
+        };
+
+        let rel_path = path.strip_prefix(base_dir).unwrap_or(path);
+        let name = path.file_name()?.to_str()?;
+        let rel_path_str = rel_path.to_string_lossy();
+
+        include_patterns
+            .iter()
+            .find(|pattern| pattern.matches(name, &rel_path_str))
+            .map(|pattern| ExclusionReason {
+                pattern: pattern.original.clone(),
+                category: "Included".to_string(),
+            })
+    }
+
+    /// Returns whether a directory matches an exclusion rule specific enough to prune
+    /// its entire subtree without descending into it.
+    ///
+    /// Only patterns flagged as directory-style at construction time (plain literal
+    /// names like `node_modules`, `.git`, `target`) and the `.gitignore` rules (checked
+    /// with the path treated as a directory) are consulted — a wildcard/extension
+    /// category or custom pattern like `*.log` is left to the
+    /// normal per-entry [`get_exclusion_reason`] check, since it's meant to match
+    /// files, not prune whole subtrees. Deliberately ignores any `--include`
+    /// allowlist: an allowlist narrows which *files* are kept, but a directory not
+    /// matching it directly (e.g. `src` under an `src/**/*.rs` allowlist) may still
+    /// contain files that do.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the directory to check
+    /// * `base_dir` - Base directory for calculating relative paths
+    ///
+    /// # Returns
+    ///
+    /// * `true` - The directory (and everything beneath it) should be skipped outright
+    /// * `false` - The directory should still be descended into
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::filter::Filter;
+    /// use std::path::Path;
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let base_dir = Path::new("/project");
+    /// let git_dir = Path::new("/project/.git");
+    ///
+    /// assert!(filter.should_prune_dir(git_dir, base_dir));
+    /// ```
+    pub fn should_prune_dir<P: AsRef<Path>>(&self, path: P, base_dir: P) -> bool {
+        let path = path.as_ref();
+        let base_dir = base_dir.as_ref();
+
+        let rel_path = path.strip_prefix(base_dir).unwrap_or(path);
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        let rel_path_str = rel_path.to_string_lossy();
+
+        let matched_category = self
+            .patterns
+            .iter()
+            .filter(|pattern| pattern.is_dir_style)
+            .any(|pattern| pattern.matches(name, &rel_path_str));
+
+        if matched_category {
+            return true;
+        }
+
+        evaluate_gitignore_matchers(&self.gitignore_matchers, path, true).is_some()
+    }
 }
 
 /// Loads and parses the embedded YAML exclusion data.
@@ -436,6 +921,171 @@ pub fn get_category_for_pattern(pattern: &str) -> String {
     "Custom".to_string()
 }
 
+/// A `.gitignore`/`.ignore` matcher rooted at a single directory, paired with that
+/// directory so [`evaluate_gitignore_matchers`] only consults matchers whose root is an
+/// ancestor of the path being checked.
+///
+/// Parsing and matching is delegated to the `ignore` crate's own
+/// [`ignore::gitignore::Gitignore`] rather than a hand-rolled glob translator, so
+/// edge cases like `**`, character classes, and escaped metacharacters behave exactly
+/// like `git` itself.
+///
+/// `pub` because it's the element type of [`Filter::with_gitignore_rules`]'s parameter
+/// and [`load_gitignore_rules`]'s return value, both of which are reachable from
+/// outside this module.
+pub type GitignoreMatcher = (std::path::PathBuf, ignore::gitignore::Gitignore);
+
+/// Evaluates `matchers` against `path`, deepest directory first: the deepest matcher
+/// with a rule matching `path` decides, so a nested `.gitignore` overrides its
+/// ancestors' rules for the paths it matches, while paths it doesn't match still fall
+/// through to its ancestors (matching `git`'s own precedence). Within a single
+/// matcher, last-match-wins negation is handled internally by the `ignore` crate.
+///
+/// Returns the category/pattern that decided the match, or `None` if nothing in
+/// `matchers` applies (or a whitelist/negation rule re-included the path).
+fn evaluate_gitignore_matchers(
+    matchers: &[GitignoreMatcher],
+    path: &Path,
+    is_dir: bool,
+) -> Option<ExclusionReason> {
+    for (root, matcher) in matchers.iter().rev() {
+        if !path.starts_with(root) {
+            continue;
+        }
+
+        match matcher.matched_path_or_any_parents(path, is_dir) {
+            ignore::Match::Ignore(glob) => {
+                return Some(ExclusionReason {
+                    pattern: glob.original().to_string(),
+                    category: format!(
+                        "Gitignore ({})",
+                        glob.from()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_else(|| root.display().to_string())
+                    ),
+                });
+            }
+            ignore::Match::Whitelist(_) => return None,
+            ignore::Match::None => continue,
+        }
+    }
+
+    None
+}
+
+/// Recursively loads a [`GitignoreMatcher`] for every directory under `root` that has
+/// its own `.gitignore`/`.ignore` file, plus one for the user's global excludes file
+/// and `.git/info/exclude`, in the precedence order `git` itself uses (global and
+/// `info/exclude` first, since they're checked last by
+/// [`evaluate_gitignore_matchers`]'s deepest-first search and therefore have the
+/// lowest precedence).
+///
+/// The `.git` directory itself is never descended into, since its contents are
+/// already excluded by the default `vcs` category.
+///
+/// # Arguments
+///
+/// * `root` - Directory to start the recursive search from
+///
+/// # Returns
+///
+/// Matchers collected from the global excludes file, `.git/info/exclude`, and every
+/// ignore file under `root`, in that order. The first entry is always the combined
+/// global/`info/exclude` matcher, which holds no rules if neither of those files
+/// exists.
+///
+/// # Examples
+///
+/// ```rust
+/// use context_generator::filter::load_gitignore_rules;
+/// use std::path::Path;
+///
+/// let rules = load_gitignore_rules(Path::new("."));
+/// ```
+pub fn load_gitignore_rules(root: &Path) -> Vec<GitignoreMatcher> {
+    let mut matchers = Vec::new();
+
+    let mut global_builder = ignore::gitignore::GitignoreBuilder::new(root);
+    if let Some(global_path) = global_excludes_path() {
+        global_builder.add(global_path);
+    }
+    global_builder.add(root.join(".git").join("info").join("exclude"));
+    if let Ok(matcher) = global_builder.build() {
+        matchers.push((root.to_path_buf(), matcher));
+    }
+
+    collect_gitignore_matchers(root, &mut matchers);
+    matchers
+}
+
+/// Resolves the path to the user's global git excludes file, the way `git` itself
+/// does: `git config --get core.excludesFile` if set, otherwise
+/// `$XDG_CONFIG_HOME/git/ignore` (or its platform equivalent).
+fn global_excludes_path() -> Option<std::path::PathBuf> {
+    if let Ok(output) = std::process::Command::new("git")
+        .args(["config", "--get", "core.excludesFile"])
+        .output()
+    {
+        if output.status.success() {
+            let configured = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !configured.is_empty() {
+                return Some(expand_tilde(&configured));
+            }
+        }
+    }
+
+    dirs::config_dir().map(|dir| dir.join("git").join("ignore"))
+}
+
+/// Expands a leading `~/` in `path` to the user's home directory, the way `git`
+/// resolves `core.excludesFile` entries. Paths without a leading `~/` are returned
+/// unchanged.
+fn expand_tilde(path: &str) -> std::path::PathBuf {
+    match path.strip_prefix("~/").and_then(|rest| dirs::home_dir().map(|home| home.join(rest))) {
+        Some(expanded) => expanded,
+        None => std::path::PathBuf::from(path),
+    }
+}
+
+fn collect_gitignore_matchers(dir: &Path, matchers: &mut Vec<GitignoreMatcher>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut subdirs = Vec::new();
+    let mut builder = ignore::gitignore::GitignoreBuilder::new(dir);
+    let mut found = false;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        if path.is_dir() && file_name != ".git" {
+            subdirs.push(path);
+        }
+    }
+
+    for name in IGNORE_FILE_NAMES {
+        let path = dir.join(name);
+        if path.is_file() {
+            found = true;
+            builder.add(&path);
+        }
+    }
+
+    if found {
+        if let Ok(matcher) = builder.build() {
+            matchers.push((dir.to_path_buf(), matcher));
+        }
+    }
+
+    for subdir in subdirs {
+        collect_gitignore_matchers(&subdir, matchers);
+    }
+}
+
 /// Validates a list of category IDs and returns any invalid ones.
 ///
 /// This function checks if the provided category IDs exist in the exclusion
@@ -734,4 +1384,48 @@ mod tests {
         assert!(go_category.patterns.contains(&"go.sum".to_string()));
         assert!(go_category.patterns.contains(&"*.test".to_string()));
     }
+
+    #[test]
+    fn test_gitignore_negation_within_single_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "context-generator-test-gitignore-negation-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".gitignore"), "*.log\n!important.log\n").unwrap();
+
+        let filter =
+            Filter::new(vec![]).unwrap().with_gitignore_rules(load_gitignore_rules(&dir));
+
+        assert!(filter.should_exclude(dir.join("other.log"), dir.clone(), false));
+        assert!(!filter.should_exclude(dir.join("important.log"), dir.clone(), false));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_nested_gitignore_overrides_ancestor_for_matched_paths() {
+        let dir = std::env::temp_dir().join(format!(
+            "context-generator-test-gitignore-nested-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let subdir = dir.join("sub");
+        std::fs::create_dir_all(&subdir).unwrap();
+        std::fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(subdir.join(".gitignore"), "!keep.log\n").unwrap();
+
+        let filter =
+            Filter::new(vec![]).unwrap().with_gitignore_rules(load_gitignore_rules(&dir));
+
+        // The nested .gitignore's negation re-includes keep.log even though the root
+        // .gitignore would have excluded every *.log.
+        assert!(!filter.should_exclude(subdir.join("keep.log"), dir.clone(), false));
+        // A sibling .log file in the same subdirectory isn't re-included, so the root
+        // rule still applies to it.
+        assert!(filter.should_exclude(subdir.join("other.log"), dir.clone(), false));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -54,10 +54,16 @@
 //!
 //! # Architecture
 //!
-//! The crate is organized into three main modules:
+//! The crate is organized into nine main modules:
 //!
 //! - [`filter`]: Pattern matching and exclusion logic
 //! - [`scanner`]: File system traversal and content processing
+//! - [`output`]: Pluggable `--format` serialization (Markdown, XML, JSON, plain)
+//! - [`config`]: Layered `.contextgen.toml` configuration
+//! - [`tokens`]: Token-budget estimation and `--max-tokens` truncation
+//! - [`commands`]: `--command` shell output as a synthetic context source
+//! - [`clipboard`]: `--clipboard` system clipboard output sink
+//! - [`plugin`]: `--plugin` external filter/formatter processes over a stdio protocol
 //! - [`cli`]: Command-line interface implementation
 //!
 //! # Exclusion Categories
@@ -73,9 +79,17 @@
 //! Use `context-generator list-exclusions` to see all available categories.
 
 pub mod cli;
+pub mod clipboard;
+pub mod commands;
+pub mod config;
 pub mod filter;
+pub mod output;
+pub mod plugin;
 pub mod scanner;
+pub mod tokens;
 
 pub use cli::run_cli;
+pub use config::Config;
 pub use filter::{ExclusionCategory, ExclusionReason, Filter};
+pub use output::OutputGenerator;
 pub use scanner::Scanner;
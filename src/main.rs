@@ -4,8 +4,14 @@
 //! entry point that delegates to the CLI module for argument parsing and execution.
 
 mod cli;
+mod clipboard;
+mod commands;
+mod config;
 mod filter;
+mod output;
+mod plugin;
 mod scanner;
+mod tokens;
 
 use std::process;
 
@@ -0,0 +1,313 @@
+//! Pluggable output-format subsystem.
+//!
+//! This module defines the [`OutputGenerator`] trait used to serialize a set of
+//! [`ScannedFile`]s into a specific shape (plain text, Markdown, XML, JSON, ...).
+//! `cli` maps `--format <id>` values to generators through [`get_generator`], so new
+//! formats can be added here without touching `scanner` or `filter`.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use context_generator::output::get_generator;
+//! use context_generator::scanner::ScannedFile;
+//!
+//! let files = vec![ScannedFile {
+//!     path: "src/main.rs".to_string(),
+//!     content: "fn main() {}".to_string(),
+//! }];
+//!
+//! let generator = get_generator("markdown").unwrap();
+//! let mut buffer = Vec::new();
+//! generator.render(&files, &mut buffer).unwrap();
+//! ```
+
+use crate::scanner::ScannedFile;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+/// Separator string used between file sections in the [`PlainGenerator`] output.
+const SEPARATOR: &str = "--------------------";
+
+/// Default `--format` identifier, used when the user does not pass `--format`.
+pub const DEFAULT_FORMAT: &str = "plain";
+
+/// A pluggable renderer that serializes scanned files into a specific output shape.
+///
+/// Implement this trait and register an instance in [`get_generator`] (and its id in
+/// [`available_formats`]) to add a new `--format` value. Renderers never touch the file system or the `Filter`/`Scanner`
+/// pipeline; they only see the files that already survived it.
+pub trait OutputGenerator {
+    /// Short identifier used as the `--format` value (e.g. `"markdown"`).
+    fn id(&self) -> &str;
+
+    /// Renders `files` to `w`.
+    fn render(&self, files: &[ScannedFile], w: &mut dyn Write) -> anyhow::Result<()>;
+}
+
+/// Renders files using the tool's original plain-text layout:
+///
+/// ```text
+/// --------------------
+/// file: src/main.rs
+/// --------------------
+///     fn main() {}
+/// --------------------
+/// ```
+pub struct PlainGenerator;
+
+impl OutputGenerator for PlainGenerator {
+    fn id(&self) -> &str {
+        "plain"
+    }
+
+    fn render(&self, files: &[ScannedFile], w: &mut dyn Write) -> anyhow::Result<()> {
+        for file in files {
+            writeln!(w, "{SEPARATOR}")?;
+            writeln!(w, "file: {}", file.path)?;
+            writeln!(w, "{SEPARATOR}")?;
+
+            for line in file.content.lines() {
+                writeln!(w, "    {line}")?;
+            }
+        }
+
+        writeln!(w, "{SEPARATOR}")?;
+        Ok(())
+    }
+}
+
+/// Renders files as Markdown, one `###` heading plus a fenced code block per file.
+/// The fence's language tag is inferred from the file extension so that code hosts
+/// and AI assistants get syntax highlighting for free.
+pub struct MarkdownGenerator;
+
+impl OutputGenerator for MarkdownGenerator {
+    fn id(&self) -> &str {
+        "markdown"
+    }
+
+    fn render(&self, files: &[ScannedFile], w: &mut dyn Write) -> anyhow::Result<()> {
+        for file in files {
+            let fence = "`".repeat(fence_len_for(&file.content));
+            writeln!(w, "### {}", file.path)?;
+            writeln!(w, "{fence}{}", language_for_path(&file.path))?;
+            write!(w, "{}", file.content)?;
+            if !file.content.ends_with('\n') {
+                writeln!(w)?;
+            }
+            writeln!(w, "{fence}")?;
+            writeln!(w)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the backtick-fence length needed to safely wrap `content` in a fenced code
+/// block: one longer than the longest run of backticks found in `content`, per
+/// CommonMark's rule that a fence must be longer than any backtick run it encloses so
+/// the block can't be closed early by the file's own content. Never shorter than 3,
+/// the minimum valid fence length.
+fn fence_len_for(content: &str) -> usize {
+    let mut longest_run = 0;
+    let mut current_run = 0;
+
+    for c in content.chars() {
+        if c == '`' {
+            current_run += 1;
+            longest_run = longest_run.max(current_run);
+        } else {
+            current_run = 0;
+        }
+    }
+
+    (longest_run + 1).max(3)
+}
+
+/// Renders files wrapped in `<file path="...">` tags, the shape several LLM tools
+/// (e.g. Anthropic's own documentation examples) expect when ingesting source context.
+pub struct XmlGenerator;
+
+impl OutputGenerator for XmlGenerator {
+    fn id(&self) -> &str {
+        "xml"
+    }
+
+    fn render(&self, files: &[ScannedFile], w: &mut dyn Write) -> anyhow::Result<()> {
+        writeln!(w, "<files>")?;
+        for file in files {
+            writeln!(w, "  <file path=\"{}\">", escape_xml(&file.path))?;
+            writeln!(w, "{}", escape_xml(&file.content))?;
+            writeln!(w, "  </file>")?;
+        }
+        writeln!(w, "</files>")?;
+        Ok(())
+    }
+}
+
+/// Renders files as a JSON array of `{"path": ..., "language": ..., "content": ...}`
+/// objects, `language` being the same extension-derived tag [`MarkdownGenerator`] uses
+/// for its fenced code blocks.
+pub struct JsonGenerator;
+
+/// A single [`ScannedFile`] plus its inferred language tag, the shape
+/// [`JsonGenerator`] serializes.
+#[derive(Serialize)]
+struct JsonFile<'a> {
+    path: &'a str,
+    language: &'static str,
+    content: &'a str,
+}
+
+impl OutputGenerator for JsonGenerator {
+    fn id(&self) -> &str {
+        "json"
+    }
+
+    fn render(&self, files: &[ScannedFile], w: &mut dyn Write) -> anyhow::Result<()> {
+        let entries: Vec<JsonFile> = files
+            .iter()
+            .map(|file| JsonFile {
+                path: &file.path,
+                language: language_for_path(&file.path),
+                content: &file.content,
+            })
+            .collect();
+
+        let value = serde_json::to_string_pretty(&entries)
+            .map_err(|e| anyhow::anyhow!("Error serializing scanned files to JSON: {e}"))?;
+        writeln!(w, "{value}")?;
+        Ok(())
+    }
+}
+
+/// Maps a file path's extension to a Markdown fenced-code-block language tag.
+///
+/// Unknown or missing extensions fall back to an empty tag, which Markdown renders
+/// as a plain (unhighlighted) code block.
+fn language_for_path(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+
+    match extension {
+        "rs" => "rust",
+        "go" => "go",
+        "py" => "python",
+        "js" => "javascript",
+        "jsx" => "jsx",
+        "ts" => "typescript",
+        "tsx" => "tsx",
+        "java" => "java",
+        "c" => "c",
+        "h" => "c",
+        "cpp" | "cc" | "cxx" => "cpp",
+        "hpp" => "cpp",
+        "rb" => "ruby",
+        "php" => "php",
+        "sh" | "bash" => "bash",
+        "yaml" | "yml" => "yaml",
+        "json" => "json",
+        "toml" => "toml",
+        "md" => "markdown",
+        "html" => "html",
+        "css" => "css",
+        "sql" => "sql",
+        _ => "",
+    }
+}
+
+/// Escapes the characters XML requires for use inside text content and attributes,
+/// including `"` and `'` so a path or content containing a quote can't break out of
+/// the `<file path="...">` attribute.
+fn escape_xml(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Returns the generator registered for `id`, or `None` if `id` is not a known format.
+///
+/// # Examples
+///
+/// ```rust
+/// use context_generator::output::get_generator;
+///
+/// assert!(get_generator("markdown").is_some());
+/// assert!(get_generator("not-a-format").is_none());
+/// ```
+pub fn get_generator(id: &str) -> Option<Box<dyn OutputGenerator>> {
+    match id {
+        "plain" => Some(Box::new(PlainGenerator)),
+        "markdown" => Some(Box::new(MarkdownGenerator)),
+        "xml" => Some(Box::new(XmlGenerator)),
+        "json" => Some(Box::new(JsonGenerator)),
+        _ => None,
+    }
+}
+
+/// Returns the `--format` identifiers of every registered generator, in the order
+/// they should be presented in help text (plain first, since it is the default).
+///
+/// # Examples
+///
+/// ```rust
+/// use context_generator::output::available_formats;
+///
+/// assert_eq!(available_formats(), vec!["plain", "markdown", "xml", "json"]);
+/// ```
+pub fn available_formats() -> Vec<&'static str> {
+    vec!["plain", "markdown", "xml", "json"]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_xml_escapes_quotes() {
+        assert_eq!(escape_xml(r#"a "b" & 'c' <d>"#), "a &quot;b&quot; &amp; &apos;c&apos; &lt;d&gt;");
+    }
+
+    #[test]
+    fn test_xml_generator_quoted_path_stays_well_formed() {
+        let files = vec![ScannedFile {
+            path: r#"weird"path.txt"#.to_string(),
+            content: "hello".to_string(),
+        }];
+
+        let mut buffer = Vec::new();
+        XmlGenerator.render(&files, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert!(output.contains(r#"path="weird&quot;path.txt""#));
+    }
+
+    #[test]
+    fn test_fence_len_for_widens_past_embedded_backticks() {
+        assert_eq!(fence_len_for("no backticks here"), 3);
+        assert_eq!(fence_len_for("a ``` code fence inside"), 4);
+        assert_eq!(fence_len_for("a ```` four-backtick run"), 5);
+    }
+
+    #[test]
+    fn test_markdown_generator_widens_fence_for_embedded_code_block() {
+        let files = vec![ScannedFile {
+            path: "README.md".to_string(),
+            content: "# Title\n\n```rust\nfn main() {}\n```\n".to_string(),
+        }];
+
+        let mut buffer = Vec::new();
+        MarkdownGenerator.render(&files, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        // The outer fence must be longer than the embedded ``` so it isn't closed early.
+        assert!(output.starts_with("### README.md\n````markdown\n"));
+        assert!(output.contains("\n````\n"));
+    }
+}
@@ -0,0 +1,349 @@
+//! External filter/formatter plugins over a JSON-RPC-style stdio protocol.
+//!
+//! A plugin is any executable that speaks newline-delimited JSON on its stdin/stdout,
+//! modeled on nushell's plugin protocol: the host spawns the child, sends a
+//! `handshake` message, then calls `filter`/`format` once per candidate file. A
+//! plugin that crashes, exits, or doesn't answer within [`CALL_TIMEOUT`] is marked
+//! dead and every call after that becomes a pass-through (keep the file, leave its
+//! content untouched), with a warning printed to stderr.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// Per-call timeout: a plugin that doesn't respond within this window is assumed hung
+/// and killed, with the in-flight call (and every call after it) treated as
+/// pass-through.
+const CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+struct Handshake {
+    method: &'static str,
+    params: HandshakeParams,
+}
+
+#[derive(Serialize)]
+struct HandshakeParams {
+    protocol: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct FilterRequest<'a> {
+    method: &'static str,
+    params: FilterParams<'a>,
+}
+
+#[derive(Serialize)]
+struct FilterParams<'a> {
+    path: &'a str,
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct FilterResponse {
+    include: bool,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct FormatRequest<'a> {
+    method: &'static str,
+    params: FormatParams<'a>,
+}
+
+#[derive(Serialize)]
+struct FormatParams<'a> {
+    path: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct FormatResponse {
+    content: String,
+}
+
+/// The verdict a [`Plugin::filter`] call produces: whether to keep the file, and
+/// optionally why (surfaced in `--dry-run` the same way a built-in category match is).
+#[derive(Debug, Clone)]
+pub struct FilterVerdict {
+    pub include: bool,
+    pub reason: Option<String>,
+}
+
+/// A running plugin process, talking newline-delimited JSON over its stdin/stdout.
+///
+/// Once a plugin has crashed, timed out, or sent a malformed response, it's marked
+/// dead so a desynced protocol can't produce garbage decisions for the rest of the
+/// scan; every subsequent call on a dead plugin is a no-op pass-through.
+pub struct Plugin {
+    path: String,
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<std::io::Result<String>>,
+    alive: bool,
+}
+
+impl Plugin {
+    /// Spawns the executable at `path` and sends the initial handshake message.
+    ///
+    /// Returns `Err` only if the process itself couldn't be spawned or its pipes
+    /// couldn't be opened; a handshake the plugin fails to answer just marks it dead
+    /// (see the type-level docs) rather than failing the whole scan.
+    pub fn spawn(path: &str) -> anyhow::Result<Plugin> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Error spawning plugin {path:?}: {e}"))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Plugin {path:?} did not expose a stdin pipe"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Plugin {path:?} did not expose a stdout pipe"))?;
+
+        let mut plugin = Plugin {
+            path: path.to_string(),
+            child,
+            stdin,
+            responses: spawn_reader(stdout),
+            alive: true,
+        };
+
+        let handshake = Handshake {
+            method: "handshake",
+            params: HandshakeParams {
+                protocol: "context-generator-plugin",
+                version: env!("CARGO_PKG_VERSION"),
+            },
+        };
+
+        if let Err(e) = plugin.send(&handshake) {
+            plugin.mark_dead(&format!("handshake write failed: {e}"));
+        } else if plugin.recv_line().is_none() {
+            plugin.mark_dead("no response to handshake");
+        }
+
+        Ok(plugin)
+    }
+
+    /// Asks the plugin whether to keep a file at `path` of `size` bytes.
+    ///
+    /// Returns an `include: true` pass-through verdict if the plugin is dead, crashes
+    /// mid-call, or times out.
+    pub fn filter(&mut self, path: &str, size: u64) -> FilterVerdict {
+        if !self.alive {
+            return FilterVerdict {
+                include: true,
+                reason: None,
+            };
+        }
+
+        let request = FilterRequest {
+            method: "filter",
+            params: FilterParams { path, size },
+        };
+
+        match self.call::<FilterResponse>(&request) {
+            Some(response) => FilterVerdict {
+                include: response.include,
+                reason: response.reason,
+            },
+            None => FilterVerdict {
+                include: true,
+                reason: None,
+            },
+        }
+    }
+
+    /// Asks the plugin to transform `content` before it's rendered.
+    ///
+    /// Returns `content` unchanged if the plugin is dead, crashes mid-call, times
+    /// out, or simply doesn't implement `format`.
+    pub fn format(&mut self, path: &str, content: &str) -> String {
+        if !self.alive {
+            return content.to_string();
+        }
+
+        let request = FormatRequest {
+            method: "format",
+            params: FormatParams { path, content },
+        };
+
+        match self.call::<FormatResponse>(&request) {
+            Some(response) => response.content,
+            None => content.to_string(),
+        }
+    }
+
+    fn send<T: Serialize>(&mut self, message: &T) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(message)
+            .map_err(|e| anyhow::anyhow!("Error encoding plugin message: {e}"))?;
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Error writing to plugin {:?}: {}", self.path, e))
+    }
+
+    fn recv_line(&mut self) -> Option<String> {
+        match self.responses.recv_timeout(CALL_TIMEOUT) {
+            Ok(Ok(line)) => Some(line),
+            _ => None,
+        }
+    }
+
+    fn call<R: for<'de> Deserialize<'de>>(&mut self, request: &impl Serialize) -> Option<R> {
+        if let Err(e) = self.send(request) {
+            self.mark_dead(&e.to_string());
+            return None;
+        }
+
+        let Some(line) = self.recv_line() else {
+            self.mark_dead("call timed out or the plugin process exited");
+            return None;
+        };
+
+        match serde_json::from_str(&line) {
+            Ok(response) => Some(response),
+            Err(e) => {
+                self.mark_dead(&format!("malformed response: {e}"));
+                None
+            }
+        }
+    }
+
+    fn mark_dead(&mut self, reason: &str) {
+        if self.alive {
+            eprintln!(
+                "Warning: plugin {:?} is unresponsive ({reason}); falling back to pass-through",
+                self.path
+            );
+            self.alive = false;
+            let _ = self.child.kill();
+        }
+    }
+}
+
+/// Spawns a background thread that reads newline-delimited messages from `stdout` and
+/// forwards each line over the returned channel, so a caller can bound how long it
+/// waits for a reply via `Receiver::recv_timeout` without blocking on a hung plugin.
+fn spawn_reader(stdout: ChildStdout) -> Receiver<std::io::Result<String>> {
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        loop {
+            let mut line = String::new();
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if tx.send(Ok(line.trim_end().to_string())).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                    break;
+                }
+            }
+        }
+    });
+
+    rx
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Writes `script` (a `#!/bin/sh` body) to a temp file, marks it executable, and
+    /// returns its path. The caller is responsible for removing it afterward.
+    fn write_fake_plugin(name: &str, script: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "context-generator-test-plugin-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::write(&path, format!("#!/bin/sh\n{script}")).unwrap();
+        let mut perms = std::fs::metadata(&path).unwrap().permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(&path, perms).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_plugin_crashing_before_handshake_is_pass_through() {
+        let path = write_fake_plugin("crash-early", "exit 1\n");
+
+        let mut plugin = Plugin::spawn(path.to_str().unwrap()).unwrap();
+        let verdict = plugin.filter("some/file.rs", 123);
+
+        assert!(verdict.include);
+        assert_eq!(plugin.format("some/file.rs", "content"), "content");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_crashing_mid_call_is_pass_through() {
+        let path = write_fake_plugin(
+            "crash-mid-call",
+            "read handshake_line\necho '{}'\nread filter_line\nexit 0\n",
+        );
+
+        let mut plugin = Plugin::spawn(path.to_str().unwrap()).unwrap();
+        let verdict = plugin.filter("some/file.rs", 123);
+
+        assert!(verdict.include);
+        assert!(verdict.reason.is_none());
+
+        // The plugin is marked dead after the first failed call, so a second call
+        // doesn't even try to talk to the (now-gone) process.
+        let verdict = plugin.filter("other/file.rs", 456);
+        assert!(verdict.include);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_malformed_response_is_pass_through() {
+        let path = write_fake_plugin(
+            "malformed-json",
+            "read handshake_line\necho '{}'\nread filter_line\necho 'not json'\n",
+        );
+
+        let mut plugin = Plugin::spawn(path.to_str().unwrap()).unwrap();
+        let verdict = plugin.filter("some/file.rs", 123);
+
+        assert!(verdict.include);
+        assert!(verdict.reason.is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_timeout_is_pass_through() {
+        // Sleeps past CALL_TIMEOUT before ever answering the filter call.
+        let path = write_fake_plugin(
+            "slow",
+            "read handshake_line\necho '{}'\nread filter_line\nsleep 10\necho '{\"include\":false}'\n",
+        );
+
+        let mut plugin = Plugin::spawn(path.to_str().unwrap()).unwrap();
+        let verdict = plugin.filter("some/file.rs", 123);
+
+        // Even though the (eventually dead) child would have said "exclude", the
+        // timeout must win and fall back to the pass-through "include" verdict.
+        assert!(verdict.include);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
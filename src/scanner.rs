@@ -6,13 +6,49 @@
 //! and dry-run mode for previewing what files would be processed.
 
 use crate::filter::{ExclusionReason, Filter};
+use crate::output::{self, OutputGenerator};
+use crate::plugin::Plugin;
+use crate::tokens::{self, TokenCounter};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::io::{BufRead, BufReader, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use walkdir::WalkDir;
 
-/// Separator string used between file sections in the output
-const SEPARATOR: &str = "--------------------";
+/// How often [`Scanner::with_progress`]'s callback is invoked: once every this many
+/// entries discovered or processed, rather than on every single one.
+const PROGRESS_THROTTLE: usize = 200;
+
+/// Which phase of a scan a [`ProgressReport`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanStage {
+    /// Walking the directory tree and classifying entries against the [`Filter`].
+    Walking,
+    /// Reading the contents of files that survived the walk.
+    Reading,
+}
+
+/// A progress snapshot passed to a [`Scanner::with_progress`] callback.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressReport {
+    pub stage: ScanStage,
+    /// During [`ScanStage::Walking`], how many entries the walk has discovered so far;
+    /// during [`ScanStage::Reading`], how many files were queued for reading.
+    pub entries_discovered: usize,
+    /// How many entries have finished being classified (`Walking`) or read
+    /// (`Reading`).
+    pub entries_processed: usize,
+    /// Total bytes read so far; only meaningful during [`ScanStage::Reading`].
+    pub bytes_read: u64,
+}
+
+/// Callback type registered via [`Scanner::with_progress`].
+type ProgressCallback = std::sync::Arc<dyn Fn(ProgressReport) + Send + Sync>;
 
 /// File system scanner that generates formatted context output.
 ///
@@ -36,6 +72,12 @@ const SEPARATOR: &str = "--------------------";
 /// ```
 pub struct Scanner {
     filter: Filter,
+    max_size: Option<u64>,
+    threads: Option<usize>,
+    plugins: Vec<Mutex<Plugin>>,
+    follow_symlinks: bool,
+    dedup: bool,
+    progress: Option<ProgressCallback>,
 }
 
 impl Scanner {
@@ -54,7 +96,136 @@ impl Scanner {
     /// let scanner = Scanner::new(filter);
     /// ```
     pub fn new(filter: Filter) -> Self {
-        Scanner { filter }
+        Scanner {
+            filter,
+            max_size: None,
+            threads: None,
+            plugins: Vec::new(),
+            follow_symlinks: false,
+            dedup: false,
+            progress: None,
+        }
+    }
+
+    /// Sets a per-file byte size cap; files larger than `max_size` are treated like
+    /// binary files and skipped (shown as excluded in `--dry-run`, under the "Size
+    /// Limit" category).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter).with_max_size(1_000_000);
+    /// ```
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Caps the number of worker threads [`Scanner::collect`] uses to read file
+    /// contents in parallel, matching the CLI's `--threads` flag. Leave unset (the
+    /// default) to use rayon's global pool, which sizes itself to the available
+    /// parallelism.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter).with_threads(4);
+    /// ```
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Registers `--plugin` processes to consult while scanning: each candidate file
+    /// is offered to every plugin's `filter` call (in order, first exclusion wins)
+    /// before its contents are read, and every plugin's `format` call gets a chance to
+    /// transform the content afterward. See the [`plugin`](crate::plugin) module for
+    /// the wire protocol and pass-through-on-crash behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter).with_plugins(vec![]);
+    /// ```
+    pub fn with_plugins(mut self, plugins: Vec<Plugin>) -> Self {
+        self.plugins = plugins.into_iter().map(Mutex::new).collect();
+        self
+    }
+
+    /// Follows symbolic links during the walk instead of treating them as opaque
+    /// leaves (`WalkDir`'s default, and this scanner's default too).
+    ///
+    /// `walkdir` detects symlink loops itself (tracked via file-system identity, not a
+    /// hop count) and reports them as a walk error rather than recursing forever; a
+    /// loop or a dangling symlink is surfaced as an excluded entry in `--dry-run`
+    /// (`Symlink: infinite recursion` / `Symlink: broken`) and skipped quietly during
+    /// a normal scan, rather than aborting the walk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter).with_follow_symlinks(true);
+    /// ```
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Collapses byte-identical included files down to a single copy of their
+    /// content, replacing every later duplicate's content with a short
+    /// `file: <path> (duplicate of <first path>)` stub so repeated vendored or
+    /// generated copies don't cost tokens more than once. Off by default, since
+    /// hashing every included file's content has a real cost on large trees.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter).with_dedup(true);
+    /// ```
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Registers a callback invoked roughly every [`PROGRESS_THROTTLE`] entries during
+    /// any walk of the directory tree ([`Scanner::collect`], [`Scanner::tree`],
+    /// [`Scanner::dry_run_as`], or a caller driving [`Scanner::entries`] directly) and
+    /// during [`Scanner::collect`]'s file-reading pass, with a [`ProgressReport`]
+    /// snapshot, so a CLI front-end can render a live status line on large trees. Unset
+    /// by default (a no-op), leaving `scan`/`dry_run` output untouched. Wired up by the
+    /// `--progress` CLI flag.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter).with_progress(|report| {
+    ///     eprintln!("{:?}: {} discovered", report.stage, report.entries_discovered);
+    /// });
+    /// ```
+    pub fn with_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(ProgressReport) + Send + Sync + 'static,
+    {
+        self.progress = Some(std::sync::Arc::new(callback));
+        self
     }
 
     /// Scans a directory and generates formatted context output.
@@ -115,6 +286,137 @@ impl Scanner {
         directory: P,
         writer: &mut W,
     ) -> anyhow::Result<()> {
+        self.render(directory, output::DEFAULT_FORMAT, writer)
+    }
+
+    /// Scans a directory like [`Scanner::scan`], but serializes the result with the
+    /// [`OutputGenerator`](crate::output::OutputGenerator) registered for `format`
+    /// instead of the default plain-text layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - Path to the directory to scan (or single file to process)
+    /// * `format` - `--format` identifier, looked up via [`crate::output::get_generator`]
+    /// * `writer` - Writer to output the formatted content to
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Successfully scanned and generated output
+    /// * `Err(anyhow::Error)` - Directory doesn't exist, unknown format, or IO error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    /// use std::io;
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter);
+    /// scanner.render("src/", "markdown", &mut io::stdout()).unwrap();
+    /// ```
+    pub fn render<P: AsRef<Path>, W: Write>(
+        &self,
+        directory: P,
+        format: &str,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        let files = self.collect(directory)?;
+        let generator = output::get_generator(format).ok_or_else(|| {
+            anyhow::anyhow!("Unknown output format {format:?}. Use --format to select one of the supported formats")
+        })?;
+
+        generator.render(&files, writer)
+    }
+
+    /// Explicit name for [`Scanner::scan`]'s parallel path: the walk that decides which
+    /// files survive the [`Filter`] still runs single-threaded (directory pruning needs
+    /// walk order), but reading their contents fans out across a rayon thread pool sized
+    /// via [`Scanner::with_threads`], with results reassembled in walk order before
+    /// rendering so the output is identical to the fully-serial path. Provided for
+    /// callers who want to call out that they're opting into the parallel reader rather
+    /// than relying on it being [`Scanner::scan`]'s default behavior.
+    pub fn scan_parallel<P: AsRef<Path>, W: Write>(
+        &self,
+        directory: P,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.scan(directory, writer)
+    }
+
+    /// Walks `directory` lazily, yielding a [`ScanEntry`] as soon as each path is
+    /// classified. This is the single walk/prune/exclude implementation behind
+    /// [`Scanner::collect`], [`Scanner::tree`], and [`Scanner::dry_run_as`], which are
+    /// all thin consumers of it — each adds only what it needs on top (reading file
+    /// contents, building a tree layout, computing token counts), so pruning, the
+    /// `--max-size` cutoff, binary detection, `--plugin` filtering, and symlink
+    /// handling stay in one place. Also usable directly by callers who want to drive
+    /// their own rendering, report progress, or stop early on very large trees.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter);
+    /// for entry in scanner.entries("src/").unwrap() {
+    ///     let entry = entry.unwrap();
+    ///     if !entry.excluded {
+    ///         println!("{}", entry.rel_path);
+    ///     }
+    /// }
+    /// ```
+    pub fn entries<P: AsRef<Path>>(&self, directory: P) -> anyhow::Result<Entries<'_>> {
+        let directory = directory.as_ref();
+
+        if !directory.exists() {
+            return Err(anyhow::anyhow!("Directory {:?} does not exist", directory));
+        }
+
+        let abs_dir = directory.canonicalize().map_err(|e| {
+            anyhow::anyhow!("Error getting absolute path for {:?}: {}", directory, e)
+        })?;
+
+        let walker = WalkDir::new(&abs_dir)
+            .follow_links(self.follow_symlinks)
+            .into_iter();
+
+        Ok(Entries {
+            scanner: self,
+            abs_dir,
+            walker,
+            discovered: 0,
+        })
+    }
+
+    /// Scans a directory (or single file) and returns the surviving files with their
+    /// text content, without writing anything.
+    ///
+    /// This is the shared collection step behind [`Scanner::scan`] and
+    /// [`Scanner::render`]: it performs the walk, applies the [`Filter`], skips binary
+    /// files, and reads the rest into memory so an
+    /// [`OutputGenerator`](crate::output::OutputGenerator) can serialize them in
+    /// whatever shape the caller picked.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - Path to the directory to scan (or single file to process)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<ScannedFile>)` - Files surviving the filter, in walk order
+    /// * `Err(anyhow::Error)` - Directory doesn't exist, permission denied, or IO error
+    ///
+    /// # Parallelism
+    ///
+    /// The directory walk and filter evaluation that decide *which* files survive run
+    /// single-threaded, since [`Filter::should_prune_dir`](crate::filter::Filter) needs
+    /// to see directories in walk order to skip whole subtrees. Reading the surviving
+    /// files' contents, the expensive part for large trees, is farmed out to a rayon
+    /// thread pool (sized via [`Scanner::with_threads`], or rayon's default otherwise).
+    /// Results are collected back into the original walk order, so `scan`'s output is
+    /// byte-for-byte identical regardless of thread count.
+    pub fn collect<P: AsRef<Path>>(&self, directory: P) -> anyhow::Result<Vec<ScannedFile>> {
         let directory = directory.as_ref();
 
         if !directory.exists() {
@@ -128,31 +430,226 @@ impl Scanner {
         // Handle case where input is a single file
         if abs_dir.is_file() {
             // Use the file itself as the base directory to match Go's filepath.Walk behavior
-            self.process_file(&abs_dir, &abs_dir, writer)?;
-            writeln!(writer, "{SEPARATOR}")?;
-            return Ok(());
+            let mut file = self.read_scanned_file(&abs_dir, &abs_dir)?;
+            if let Some(file) = file.as_mut() {
+                if !self.plugins.is_empty() {
+                    file.content = self.plugins_format(&file.path, std::mem::take(&mut file.content));
+                }
+            }
+            return Ok(file.into_iter().collect());
         }
 
-        for entry in WalkDir::new(&abs_dir) {
-            let entry = entry.map_err(|e| anyhow::anyhow!("Error walking directory: {}", e))?;
-            let path = entry.path();
-
-            if self
-                .filter
-                .should_exclude(path, &abs_dir, entry.file_type().is_dir())
-            {
-                if entry.file_type().is_dir() {
-                    continue;
-                }
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        for entry in self.entries(&abs_dir)? {
+            let entry = entry?;
+            if entry.excluded || entry.is_dir {
                 continue;
             }
+            candidates.push(abs_dir.join(&entry.rel_path));
+        }
+
+        let files = self.read_candidates(&candidates, &abs_dir)?;
+        Ok(if self.dedup {
+            dedup_scanned_files(files)
+        } else {
+            files
+        })
+    }
+
+    /// Offers `path` to every registered `--plugin` in turn, returning the first
+    /// exclusion verdict as an [`ExclusionReason`] (carrying the plugin's own `reason`
+    /// string when it gave one), or `None` if every plugin (or none at all) passes it
+    /// through. A plugin that's crashed or timed out always passes the file through
+    /// (see [`plugin::Plugin::filter`](crate::plugin::Plugin::filter)).
+    fn plugin_exclusion_reason(&self, path: &Path) -> Option<ExclusionReason> {
+        if self.plugins.is_empty() {
+            return None;
+        }
 
-            if entry.file_type().is_file() {
-                self.process_file(path, &abs_dir, writer)?;
+        let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let path_str = path.to_string_lossy();
+
+        for plugin in &self.plugins {
+            let verdict = plugin
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .filter(&path_str, size);
+            if !verdict.include {
+                return Some(ExclusionReason {
+                    category: "Plugin".to_string(),
+                    pattern: verdict
+                        .reason
+                        .unwrap_or_else(|| "excluded by --plugin".to_string()),
+                });
             }
         }
 
-        writeln!(writer, "{SEPARATOR}")?;
+        None
+    }
+
+    /// Invokes the [`Scanner::with_progress`] callback, if one is registered, throttled
+    /// to roughly once every [`PROGRESS_THROTTLE`] processed entries (or always, when
+    /// `force` is set, so callers get a final report with the true totals).
+    fn report_progress(
+        &self,
+        stage: ScanStage,
+        entries_discovered: usize,
+        entries_processed: usize,
+        bytes_read: u64,
+        force: bool,
+    ) {
+        let Some(progress) = &self.progress else {
+            return;
+        };
+
+        if !force && entries_processed % PROGRESS_THROTTLE != 0 {
+            return;
+        }
+
+        progress(ProgressReport {
+            stage,
+            entries_discovered,
+            entries_processed,
+            bytes_read,
+        });
+    }
+
+    /// Runs `content` through every registered `--plugin`'s `format` call, in order.
+    fn plugins_format(&self, path: &str, content: String) -> String {
+        let mut content = content;
+        for plugin in &self.plugins {
+            content = plugin
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .format(path, &content);
+        }
+        content
+    }
+
+    /// Reads `candidates` into [`ScannedFile`]s in parallel, preserving `candidates`'
+    /// order in the result so output stays deterministic across thread counts.
+    fn read_candidates(
+        &self,
+        candidates: &[PathBuf],
+        abs_dir: &Path,
+    ) -> anyhow::Result<Vec<ScannedFile>> {
+        let processed = std::sync::atomic::AtomicUsize::new(0);
+        let bytes_read = std::sync::atomic::AtomicU64::new(0);
+
+        let read_all = || -> anyhow::Result<Vec<ScannedFile>> {
+            candidates
+                .par_iter()
+                .map(|path| {
+                    let mut file = self.read_scanned_file(path.as_path(), abs_dir)?;
+                    if let Some(file) = file.as_mut() {
+                        if !self.plugins.is_empty() {
+                            file.content =
+                                self.plugins_format(&file.path, std::mem::take(&mut file.content));
+                        }
+                    }
+
+                    let total_processed =
+                        processed.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                    let total_bytes = file.as_ref().map_or(0, |f| f.content.len() as u64);
+                    let total_bytes = bytes_read
+                        .fetch_add(total_bytes, std::sync::atomic::Ordering::Relaxed)
+                        + total_bytes;
+                    self.report_progress(
+                        ScanStage::Reading,
+                        candidates.len(),
+                        total_processed,
+                        total_bytes,
+                        false,
+                    );
+
+                    Ok(file)
+                })
+                .collect::<anyhow::Result<Vec<Option<ScannedFile>>>>()
+                .map(|files| files.into_iter().flatten().collect())
+        };
+
+        let result = match self.threads {
+            Some(threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|e| anyhow::anyhow!("Error building thread pool: {e}"))?
+                .install(read_all),
+            None => read_all(),
+        };
+
+        self.report_progress(
+            ScanStage::Reading,
+            candidates.len(),
+            processed.load(std::sync::atomic::Ordering::Relaxed),
+            bytes_read.load(std::sync::atomic::Ordering::Relaxed),
+            true,
+        );
+
+        result
+    }
+
+    /// Renders the set of files surviving the filter as an indented directory tree,
+    /// without reading any file contents. Intended for the CLI's `--tree` flag, which
+    /// lets users dry-run their include/exclude rules before generating a large
+    /// context blob.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - Path to the directory to analyze
+    /// * `writer` - Writer to output the tree to
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Successfully rendered the tree
+    /// * `Err(anyhow::Error)` - Directory doesn't exist, permission denied, or IO error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use context_generator::{filter::Filter, scanner::Scanner};
+    /// use std::io;
+    ///
+    /// let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+    /// let scanner = Scanner::new(filter);
+    /// scanner.tree("src/", &mut io::stdout()).unwrap();
+    /// ```
+    ///
+    /// # Output Format
+    ///
+    /// ```text
+    /// ├── src/
+    /// │   ├── cli.rs
+    /// │   └── lib.rs
+    /// └── Cargo.toml
+    /// ```
+    pub fn tree<P: AsRef<Path>, W: Write>(
+        &self,
+        directory: P,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        let directory = directory.as_ref();
+
+        if !directory.exists() {
+            return Err(anyhow::anyhow!("Directory {:?} does not exist", directory));
+        }
+
+        let mut rel_paths = Vec::new();
+        for entry in self.entries(directory)? {
+            let entry = entry?;
+            // `--tree` only shows what `collect`/`scan` would actually include, so skip
+            // directories (implied by the file paths under them), anything the filter,
+            // `--max-size`, or a `--plugin` excluded, and binary files (which `collect`
+            // skips just like an excluded one).
+            if entry.is_dir || entry.excluded || !entry.is_text || entry.rel_path.is_empty() {
+                continue;
+            }
+            rel_paths.push(entry.rel_path);
+        }
+
+        rel_paths.sort();
+
+        let root = PathTreeNode::build(&rel_paths);
+        root.print_children(writer, "")?;
         Ok(())
     }
 
@@ -205,6 +702,41 @@ impl Scanner {
         &self,
         directory: P,
         writer: &mut W,
+    ) -> anyhow::Result<()> {
+        self.dry_run_as(
+            directory,
+            output::DEFAULT_FORMAT,
+            tokens::DEFAULT_TOKEN_LIMIT,
+            writer,
+        )
+    }
+
+    /// Performs a dry-run scan like [`Scanner::dry_run`], but when `format` is
+    /// `"json"`, emits a JSON manifest of the included/excluded files instead of the
+    /// indented tree layout. Every other format falls back to the tree layout, since
+    /// Markdown/XML/plain don't have an established shape for a manifest. Either way,
+    /// each included text file is reported with its estimated `--max-tokens` cost, and
+    /// the running total against `max_tokens` so users can tune exclusions before
+    /// generating the real output.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - Path to the directory to analyze
+    /// * `format` - `--format` identifier; only `"json"` changes the output shape
+    /// * `max_tokens` - `--max-tokens` budget to report included files' running total
+    ///   against
+    /// * `writer` - Writer to output the dry-run report to
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Successfully generated dry-run report
+    /// * `Err(anyhow::Error)` - Directory doesn't exist, permission denied, or IO error
+    pub fn dry_run_as<P: AsRef<Path>, W: Write>(
+        &self,
+        directory: P,
+        format: &str,
+        max_tokens: usize,
+        writer: &mut W,
     ) -> anyhow::Result<()> {
         let directory = directory.as_ref();
 
@@ -218,61 +750,104 @@ impl Scanner {
 
         let mut included_files = Vec::new();
         let mut excluded_files = Vec::new();
+        let mut dedup_seen: HashMap<u64, (String, String)> = HashMap::new();
 
-        for entry in WalkDir::new(&abs_dir) {
-            let entry = entry.map_err(|e| anyhow::anyhow!("Error walking directory: {}", e))?;
-            let path = entry.path();
-
-            let rel_path = path
-                .strip_prefix(&abs_dir)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .to_string();
+        for entry in self.entries(&abs_dir)? {
+            let entry = entry?;
 
-            if let Some(reason) =
-                self.filter
-                    .get_exclusion_reason(path, &abs_dir, entry.file_type().is_dir())
-            {
+            if entry.excluded {
                 excluded_files.push(FileInfo {
-                    rel_path,
-                    is_dir: entry.file_type().is_dir(),
+                    rel_path: entry.rel_path,
+                    is_dir: entry.is_dir,
                     is_text: false,
                     excluded: true,
-                    reason: Some(reason),
+                    reason: entry.reason,
+                    tokens: None,
+                    symlink_target: entry.symlink_target,
                 });
                 continue;
             }
 
-            let is_text = if entry.file_type().is_file() {
-                self.is_text_file(path)?
+            let text_content = if entry.is_text {
+                std::fs::read_to_string(abs_dir.join(&entry.rel_path)).ok()
             } else {
-                false
+                None
             };
 
+            let tokens = text_content
+                .as_ref()
+                .map(|content| tokens::BpeTokenCounter::new().count(content));
+
+            let mut reason = entry.reason;
+            if self.dedup {
+                if let Some(content) = &text_content {
+                    let hash = content_hash(content);
+                    match dedup_seen.entry(hash) {
+                        std::collections::hash_map::Entry::Occupied(occupied) => {
+                            let (first_rel_path, first_content) = occupied.get();
+                            if first_content == content {
+                                reason = Some(ExclusionReason {
+                                    pattern: format!("duplicate of {first_rel_path}"),
+                                    category: "Duplicate".to_string(),
+                                });
+                            }
+                        }
+                        std::collections::hash_map::Entry::Vacant(vacant) => {
+                            vacant.insert((entry.rel_path.clone(), content.clone()));
+                        }
+                    }
+                }
+            }
+
             included_files.push(FileInfo {
-                rel_path,
-                is_dir: entry.file_type().is_dir(),
-                is_text,
+                rel_path: entry.rel_path,
+                is_dir: entry.is_dir,
+                is_text: entry.is_text,
                 excluded: false,
-                reason: None,
+                reason,
+                tokens,
+                symlink_target: entry.symlink_target,
             });
         }
 
-        self.print_dry_run_results(&included_files, &excluded_files, directory, writer)?;
+        if format == "json" {
+            self.print_dry_run_manifest(
+                &included_files,
+                &excluded_files,
+                directory,
+                max_tokens,
+                writer,
+            )?;
+        } else {
+            self.print_dry_run_results(
+                &included_files,
+                &excluded_files,
+                directory,
+                max_tokens,
+                writer,
+            )?;
+        }
         Ok(())
     }
 
-    fn process_file<P: AsRef<Path>, W: Write>(
+    /// Reads a single file into a [`ScannedFile`] if it passes the text-file check.
+    ///
+    /// Returns `Ok(None)` for binary files, which [`Scanner::collect`] simply skips,
+    /// matching the previous `process_file` behavior of silently omitting them.
+    fn read_scanned_file<P: AsRef<Path>>(
         &self,
         path: P,
         base_dir: P,
-        writer: &mut W,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Option<ScannedFile>> {
         let path = path.as_ref();
         let base_dir = base_dir.as_ref();
 
+        if self.exceeds_max_size(path)? {
+            return Ok(None);
+        }
+
         if !self.is_text_file(path)? {
-            return Ok(());
+            return Ok(None);
         }
 
         let rel_path = path
@@ -283,20 +858,34 @@ impl Scanner {
         // If rel_path is empty (happens when path == base_dir), use "." like Go does
         let display_path = if rel_path.is_empty() { "." } else { &rel_path };
 
-        writeln!(writer, "{SEPARATOR}")?;
-        writeln!(writer, "file: {display_path}")?;
-        writeln!(writer, "{SEPARATOR}")?;
-
         let file = File::open(path)
             .map_err(|e| anyhow::anyhow!("Error opening file {:?}: {}", path, e))?;
         let reader = BufReader::new(file);
 
+        let mut content = String::new();
         for line in reader.lines() {
             let line = line.map_err(|e| anyhow::anyhow!("Error reading file {:?}: {}", path, e))?;
-            writeln!(writer, "    {line}")?;
+            content.push_str(&line);
+            content.push('\n');
         }
 
-        Ok(())
+        Ok(Some(ScannedFile {
+            path: display_path.to_string(),
+            content,
+        }))
+    }
+
+    /// Returns `true` if `path`'s size exceeds the configured [`Scanner::with_max_size`]
+    /// cap. Always `false` when no cap was configured.
+    fn exceeds_max_size(&self, path: &Path) -> anyhow::Result<bool> {
+        let Some(max_size) = self.max_size else {
+            return Ok(false);
+        };
+
+        let metadata = std::fs::metadata(path)
+            .map_err(|e| anyhow::anyhow!("Error reading metadata for {:?}: {}", path, e))?;
+
+        Ok(metadata.len() > max_size)
     }
 
     fn is_text_file<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<bool> {
@@ -318,11 +907,53 @@ impl Scanner {
         Ok(content_type.is_text())
     }
 
+    /// Writes `included_files`/`excluded_files` as a JSON manifest, the `--format
+    /// json` counterpart of [`Scanner::print_dry_run_results`]'s tree layout.
+    fn print_dry_run_manifest<W: Write>(
+        &self,
+        included_files: &[FileInfo],
+        excluded_files: &[FileInfo],
+        directory: &Path,
+        max_tokens: usize,
+        writer: &mut W,
+    ) -> anyhow::Result<()> {
+        #[derive(Serialize)]
+        struct DryRunManifest<'a> {
+            directory: String,
+            included: &'a [FileInfo],
+            excluded: &'a [FileInfo],
+            total_tokens: usize,
+            max_tokens: usize,
+        }
+
+        let mut included_sorted = included_files.to_vec();
+        let mut excluded_sorted = excluded_files.to_vec();
+
+        included_sorted.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+        excluded_sorted.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+
+        let total_tokens: usize = included_sorted.iter().filter_map(|f| f.tokens).sum();
+
+        let manifest = DryRunManifest {
+            directory: directory.display().to_string(),
+            included: &included_sorted,
+            excluded: &excluded_sorted,
+            total_tokens,
+            max_tokens,
+        };
+
+        let value = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| anyhow::anyhow!("Error serializing dry-run manifest to JSON: {e}"))?;
+        writeln!(writer, "{value}")?;
+        Ok(())
+    }
+
     fn print_dry_run_results<W: Write>(
         &self,
         included_files: &[FileInfo],
         excluded_files: &[FileInfo],
         directory: &Path,
+        max_tokens: usize,
         writer: &mut W,
     ) -> anyhow::Result<()> {
         let mut included_sorted = included_files.to_vec();
@@ -337,9 +968,15 @@ impl Scanner {
         if included_sorted.is_empty() {
             writeln!(writer, "  (none)")?;
         } else {
-            self.print_tree_files(&included_sorted, false, writer)?;
+            self.print_tree_files(&included_sorted, true, writer)?;
         }
 
+        let total_tokens: usize = included_sorted.iter().filter_map(|f| f.tokens).sum();
+        writeln!(
+            writer,
+            "\nEstimated tokens for files that would be processed: ~{total_tokens} / {max_tokens}"
+        )?;
+
         writeln!(writer, "\nFiles that would be excluded:")?;
         if excluded_sorted.is_empty() {
             writeln!(writer, "  (none)")?;
@@ -486,6 +1123,11 @@ impl Scanner {
             } else if !file.is_text && !file.excluded {
                 name.push_str(" (binary, will be skipped)");
             }
+
+            if let Some(target) = &file.symlink_target {
+                name.push_str(" -> ");
+                name.push_str(target);
+            }
         } else if node.is_dir {
             name.push('/');
         }
@@ -494,6 +1136,9 @@ impl Scanner {
 
         if show_reason {
             if let Some(file) = &node.file {
+                if let Some(tokens) = file.tokens {
+                    write!(writer, " (~{tokens} tokens)")?;
+                }
                 if let Some(reason) = &file.reason {
                     write!(writer, " [{}: {}]", reason.category, reason.pattern)?;
                 }
@@ -505,13 +1150,302 @@ impl Scanner {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Reads the target of `entry`'s symlink, if it is one. Returns `None` for anything
+/// else (including a broken link, which [`symlink_walk_error`] handles separately).
+fn read_symlink_target(entry: &walkdir::DirEntry) -> Option<String> {
+    if !entry.path_is_symlink() {
+        return None;
+    }
+
+    std::fs::read_link(entry.path())
+        .ok()
+        .map(|target| target.display().to_string())
+}
+
+/// Classifies a [`walkdir::Error`] raised while following symlinks (`Scanner`'s
+/// `follow_symlinks` mode) as either a symlink-specific problem `--dry-run` can report
+/// as an excluded entry, or `None` if it's some other error the caller should still
+/// abort the walk for.
+fn symlink_walk_error(error: &walkdir::Error, abs_dir: &Path) -> Option<(String, ExclusionReason)> {
+    let path = error.path()?;
+    let rel_path = path
+        .strip_prefix(abs_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string();
+
+    if let Some(ancestor) = error.loop_ancestor() {
+        return Some((
+            rel_path,
+            ExclusionReason {
+                pattern: format!("-> {}", ancestor.display()),
+                category: "Symlink: infinite recursion".to_string(),
+            },
+        ));
+    }
+
+    if error.io_error().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) {
+        return Some((
+            rel_path,
+            ExclusionReason {
+                pattern: "broken symlink target".to_string(),
+                category: "Symlink: broken".to_string(),
+            },
+        ));
+    }
+
+    None
+}
+
+/// Hashes `content` with a fast, non-cryptographic hasher. Used only as a pre-filter
+/// before [`dedup_scanned_files`] confirms a match with an exact string comparison, so
+/// a hash collision can never cause two distinct files to be merged.
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Replaces every duplicate of an included file's content (after the first occurrence)
+/// with a short `file: <path> (duplicate of <first path>)` stub, so the same vendored
+/// or generated file doesn't cost tokens more than once.
+///
+/// Candidates are grouped first by content length (free, since every [`ScannedFile`]
+/// already carries it), then, within a length group, by [`content_hash`]; files that
+/// land in the same hash bucket are only treated as duplicates once their content is
+/// confirmed byte-for-byte equal, so a hash collision never merges distinct files.
+fn dedup_scanned_files(files: Vec<ScannedFile>) -> Vec<ScannedFile> {
+    let mut by_size: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        by_size.entry(file.content.len()).or_default().push(i);
+    }
+
+    let mut first_of: HashMap<usize, usize> = HashMap::new();
+
+    for indices in by_size.into_values() {
+        if indices.len() < 2 {
+            continue;
+        }
+
+        let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        for i in indices {
+            by_hash
+                .entry(content_hash(&files[i].content))
+                .or_default()
+                .push(i);
+        }
+
+        for group in by_hash.into_values() {
+            let Some(&first) = group.first() else {
+                continue;
+            };
+            for &i in &group[1..] {
+                if files[i].content == files[first].content {
+                    first_of.insert(i, first);
+                }
+            }
+        }
+    }
+
+    let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+
+    files
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut file)| {
+            if let Some(&first) = first_of.get(&i) {
+                file.content = format!("file: {} (duplicate of {})\n", file.path, paths[first]);
+            }
+            file
+        })
+        .collect()
+}
+
+/// A single entry discovered while walking a directory via [`Scanner::entries`],
+/// before any file content is read.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanEntry {
+    /// Path relative to the scanned directory.
+    pub rel_path: String,
+    pub is_dir: bool,
+    /// `false` for directories and binary files; meaningless when `excluded` is `true`
+    /// and the entry was never content-sniffed.
+    pub is_text: bool,
+    pub excluded: bool,
+    /// Why this entry was excluded, or why it matched an `--include` allowlist.
+    pub reason: Option<ExclusionReason>,
+    /// Where this entry's symlink points, if it is one and `--follow-symlinks` is on.
+    pub symlink_target: Option<String>,
+}
+
+/// Iterator returned by [`Scanner::entries`]. See its docs for what each
+/// [`ScanEntry`] carries.
+pub struct Entries<'s> {
+    scanner: &'s Scanner,
+    abs_dir: PathBuf,
+    walker: walkdir::IntoIter,
+    discovered: usize,
+}
+
+impl Iterator for Entries<'_> {
+    type Item = anyhow::Result<ScanEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.walker.next() {
+            Some(entry) => entry,
+            None => {
+                self.scanner.report_progress(
+                    ScanStage::Walking,
+                    self.discovered,
+                    self.discovered,
+                    0,
+                    true,
+                );
+                return None;
+            }
+        };
+
+        self.discovered += 1;
+        self.scanner.report_progress(
+            ScanStage::Walking,
+            self.discovered,
+            self.discovered,
+            0,
+            false,
+        );
+
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) if self.scanner.follow_symlinks => {
+                if let Some((rel_path, reason)) = symlink_walk_error(&e, &self.abs_dir) {
+                    return Some(Ok(ScanEntry {
+                        rel_path,
+                        is_dir: false,
+                        is_text: false,
+                        excluded: true,
+                        reason: Some(reason),
+                        symlink_target: None,
+                    }));
+                }
+                return Some(Err(anyhow::anyhow!("Error walking directory: {}", e)));
+            }
+            Err(e) => return Some(Err(anyhow::anyhow!("Error walking directory: {}", e))),
+        };
+
+        let path = entry.path();
+        let is_dir = entry.file_type().is_dir();
+
+        let rel_path = path
+            .strip_prefix(&self.abs_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+
+        if is_dir && self.scanner.filter.should_prune_dir(path, &self.abs_dir) {
+            self.walker.skip_current_dir();
+        }
+
+        let symlink_target = if self.scanner.follow_symlinks {
+            read_symlink_target(&entry)
+        } else {
+            None
+        };
+
+        if let Some(reason) = self
+            .scanner
+            .filter
+            .get_exclusion_reason(path, &self.abs_dir, is_dir)
+        {
+            return Some(Ok(ScanEntry {
+                rel_path,
+                is_dir,
+                is_text: false,
+                excluded: true,
+                reason: Some(reason),
+                symlink_target,
+            }));
+        }
+
+        if entry.file_type().is_file() {
+            match self.scanner.exceeds_max_size(path) {
+                Ok(true) => {
+                    return Some(Ok(ScanEntry {
+                        rel_path,
+                        is_dir: false,
+                        is_text: false,
+                        excluded: true,
+                        reason: Some(ExclusionReason {
+                            pattern: format!(
+                                "> {} bytes",
+                                self.scanner.max_size.unwrap_or_default()
+                            ),
+                            category: "Size Limit".to_string(),
+                        }),
+                        symlink_target,
+                    }));
+                }
+                Ok(false) => {}
+                Err(e) => return Some(Err(e)),
+            }
+
+            if let Some(reason) = self.scanner.plugin_exclusion_reason(path) {
+                return Some(Ok(ScanEntry {
+                    rel_path,
+                    is_dir: false,
+                    is_text: false,
+                    excluded: true,
+                    reason: Some(reason),
+                    symlink_target,
+                }));
+            }
+        }
+
+        let is_text = if entry.file_type().is_file() {
+            match self.scanner.is_text_file(path) {
+                Ok(v) => v,
+                Err(e) => return Some(Err(e)),
+            }
+        } else {
+            false
+        };
+
+        Some(Ok(ScanEntry {
+            rel_path,
+            is_dir,
+            is_text,
+            excluded: false,
+            reason: self.scanner.filter.include_match_reason(path, &self.abs_dir),
+            symlink_target,
+        }))
+    }
+}
+
+/// A single scanned file together with its text content.
+///
+/// This is the unit [`Scanner::collect`] produces and
+/// [`OutputGenerator`](crate::output::OutputGenerator) implementations in the
+/// [`output`](crate::output) module consume to render context in different shapes.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScannedFile {
+    /// Path relative to the scanned directory (or `.` for a single scanned file).
+    pub path: String,
+    /// Full text content of the file, with each line terminated by `\n`.
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
 struct FileInfo {
     rel_path: String,
     is_dir: bool,
     is_text: bool,
     excluded: bool,
     reason: Option<ExclusionReason>,
+    /// Estimated token count, computed only for included text files so `--dry-run`
+    /// can report per-file cost against the `--max-tokens` budget.
+    tokens: Option<usize>,
+    /// Where this entry's symlink points, if it is one and `--follow-symlinks` is on,
+    /// so the tree printer can annotate it like `name -> target`.
+    symlink_target: Option<String>,
 }
 
 #[derive(Debug)]
@@ -521,3 +1455,175 @@ struct TreeNode {
     children: Vec<TreeNode>,
     is_dir: bool,
 }
+
+/// A lightweight tree node used by [`Scanner::tree`] to render surviving paths.
+///
+/// Unlike [`TreeNode`], this carries no exclusion metadata: `--tree` only shows what
+/// would be included, so there's nothing to report a reason for.
+#[derive(Debug)]
+struct PathTreeNode {
+    name: String,
+    is_file: bool,
+    children: Vec<PathTreeNode>,
+}
+
+impl PathTreeNode {
+    /// Builds a tree from a list of `/`-separated relative file paths.
+    fn build(rel_paths: &[String]) -> Self {
+        let mut root = PathTreeNode {
+            name: String::new(),
+            is_file: false,
+            children: Vec::new(),
+        };
+
+        for rel_path in rel_paths {
+            root.insert(rel_path.split('/'));
+        }
+
+        root.sort();
+        root
+    }
+
+    fn insert<'a>(&mut self, mut parts: impl Iterator<Item = &'a str> + Clone) {
+        let Some(part) = parts.next() else {
+            return;
+        };
+
+        let is_last = parts.clone().next().is_none();
+
+        let index = match self.children.iter().position(|child| child.name == part) {
+            Some(index) => index,
+            None => {
+                self.children.push(PathTreeNode {
+                    name: part.to_string(),
+                    is_file: is_last,
+                    children: Vec::new(),
+                });
+                self.children.len() - 1
+            }
+        };
+
+        if is_last {
+            self.children[index].is_file = true;
+        } else {
+            self.children[index].insert(parts);
+        }
+    }
+
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| match (a.is_file, b.is_file) {
+            (false, true) => std::cmp::Ordering::Less,
+            (true, false) => std::cmp::Ordering::Greater,
+            _ => a.name.cmp(&b.name),
+        });
+
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+
+    /// Prints this node's children using the classic `├──`/`└──`/`│` connectors,
+    /// `prefix` carrying the accumulated ancestor indentation.
+    fn print_children<W: Write>(&self, writer: &mut W, prefix: &str) -> anyhow::Result<()> {
+        let child_count = self.children.len();
+
+        for (i, child) in self.children.iter().enumerate() {
+            let is_last = i == child_count - 1;
+            let branch = if is_last { "└── " } else { "├── " };
+
+            let suffix = if child.is_file { "" } else { "/" };
+            writeln!(writer, "{prefix}{branch}{}{suffix}", child.name)?;
+
+            let child_prefix = if is_last {
+                format!("{prefix}    ")
+            } else {
+                format!("{prefix}│   ")
+            };
+
+            child.print_children(writer, &child_prefix)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::Filter;
+
+    #[test]
+    fn test_dedup_scanned_files_merges_identical_content() {
+        let files = vec![
+            ScannedFile {
+                path: "a.txt".to_string(),
+                content: "same".to_string(),
+            },
+            ScannedFile {
+                path: "b.txt".to_string(),
+                content: "same".to_string(),
+            },
+            ScannedFile {
+                path: "c.txt".to_string(),
+                content: "different".to_string(),
+            },
+        ];
+
+        let deduped = dedup_scanned_files(files);
+
+        assert_eq!(deduped[0].content, "same");
+        assert_eq!(deduped[1].content, "file: b.txt (duplicate of a.txt)\n");
+        assert_eq!(deduped[2].content, "different");
+    }
+
+    #[test]
+    fn test_dedup_scanned_files_does_not_merge_same_length_different_content() {
+        // Same byte length, same hash bucket candidacy, but distinct content: must not
+        // be treated as duplicates (content_hash is only a pre-filter).
+        let files = vec![
+            ScannedFile {
+                path: "a.txt".to_string(),
+                content: "abc".to_string(),
+            },
+            ScannedFile {
+                path: "b.txt".to_string(),
+                content: "xyz".to_string(),
+            },
+        ];
+
+        let deduped = dedup_scanned_files(files);
+
+        assert_eq!(deduped[0].content, "abc");
+        assert_eq!(deduped[1].content, "xyz");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop_is_excluded_not_fatal() {
+        let dir = std::env::temp_dir().join(format!(
+            "context-generator-test-symlink-loop-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let filter = Filter::new_with_defaults(vec![], &[]).unwrap();
+        let scanner = Scanner::new(filter).with_follow_symlinks(true);
+
+        let entries: Vec<ScanEntry> = scanner
+            .entries(&dir)
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+
+        let loop_entry = entries.iter().find(|e| e.rel_path == "loop").unwrap();
+        assert!(loop_entry.excluded);
+        assert_eq!(
+            loop_entry.reason.as_ref().unwrap().category,
+            "Symlink: infinite recursion"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
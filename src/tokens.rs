@@ -0,0 +1,421 @@
+//! Token-budget-aware context truncation.
+//!
+//! This module turns the tool from "dump everything" into something that reliably
+//! produces a prompt that fits an LLM's context window. [`fit_to_budget`] estimates
+//! a token count per [`ScannedFile`] via a pluggable [`TokenCounter`], then greedily
+//! keeps files (smallest/highest-priority first) until a `--max-tokens` budget is
+//! exhausted, reporting what got left out.
+
+use crate::scanner::ScannedFile;
+use glob::Pattern;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Default `--max-tokens` budget, applied unless `--no-token-limit` is passed:
+/// comfortably under the context window of most current assistant models.
+pub const DEFAULT_TOKEN_LIMIT: usize = 200_000;
+
+/// Estimates how many LLM tokens a chunk of text would consume.
+///
+/// This is intentionally a trait rather than a single function: [`HeuristicTokenCounter`]
+/// is a cheap approximation, [`BpeTokenCounter`] (the counter the CLI uses) is a
+/// byte-pair-encoding estimate, and a real byte-pair-encoding counter (e.g.
+/// `cl100k_base`) can be slotted in later without touching the budget logic in
+/// [`fit_to_budget`].
+pub trait TokenCounter {
+    /// Returns the estimated token count for `text`.
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Cheap token estimator: roughly 4 characters per token, nudged up for
+/// whitespace-heavy text since code/prose tends to tokenize whitespace separately
+/// from the words around it.
+pub struct HeuristicTokenCounter;
+
+impl TokenCounter for HeuristicTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        let chars = text.chars().count();
+        let whitespace = text.chars().filter(|c| c.is_whitespace()).count();
+        ((chars + whitespace) as f64 / 4.0).ceil() as usize
+    }
+}
+
+/// Byte-pair-encoding token estimator built the same way as GPT-style tokenizers
+/// (`cl100k_base`/`o200k_base`): split the text into chunks with a GPT-style
+/// pretokenizer regex, then within each chunk greedily merge the lowest-rank adjacent
+/// byte pair repeatedly until no mergeable pair remains, counting one token per
+/// surviving segment.
+///
+/// This is **not** a drop-in replacement for cl100k_base/o200k_base: it ships a small
+/// built-in table of common English/code byte pairs rather than their real
+/// tens-of-thousands-of-entries merge-rank table, so token counts will differ from
+/// those tokenizers and budgeting against a specific model's exact context window
+/// should not rely on it matching exactly. The mechanism itself (regex pretokenization
+/// + iterative byte-pair merging) is the same, so a real rank table can be dropped in
+/// later without touching [`fit_to_budget`] or this trait.
+pub struct BpeTokenCounter {
+    ranks: HashMap<(u8, u8), u32>,
+}
+
+impl Default for BpeTokenCounter {
+    fn default() -> Self {
+        BpeTokenCounter {
+            ranks: builtin_merge_ranks(),
+        }
+    }
+}
+
+impl BpeTokenCounter {
+    /// Creates a counter using the built-in approximate merge-rank table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts tokens within a single pretokenized chunk by greedily merging the
+    /// lowest-rank adjacent byte pair until none of the remaining pairs are in the
+    /// rank table.
+    fn count_chunk(&self, chunk: &str) -> usize {
+        let mut symbols: Vec<Vec<u8>> = chunk.bytes().map(|b| vec![b]).collect();
+
+        loop {
+            let mut best: Option<(usize, u32)> = None;
+
+            for i in 0..symbols.len().saturating_sub(1) {
+                let pair = (
+                    *symbols[i].last().expect("symbol is never empty"),
+                    *symbols[i + 1].first().expect("symbol is never empty"),
+                );
+                if let Some(&rank) = self.ranks.get(&pair) {
+                    if best.map(|(_, best_rank)| rank < best_rank).unwrap_or(true) {
+                        best = Some((i, rank));
+                    }
+                }
+            }
+
+            let Some((i, _)) = best else {
+                break;
+            };
+
+            let merged = [symbols[i].clone(), symbols[i + 1].clone()].concat();
+            symbols.splice(i..=i + 1, [merged]);
+        }
+
+        symbols.len()
+    }
+}
+
+impl TokenCounter for BpeTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        pretokenize(text).map(|chunk| self.count_chunk(chunk)).sum()
+    }
+}
+
+/// Splits `text` into chunks the way the cl100k/o200k pretokenizer regex does:
+/// contractions, runs of letters, runs of digits, runs of other non-whitespace
+/// symbols, and runs of whitespace, each as their own chunk. The `regex` crate has no
+/// lookahead support, so this drops the original's `(?!\S)` trailing-whitespace rule;
+/// close enough for token-count estimation.
+fn pretokenize(text: &str) -> impl Iterator<Item = &str> {
+    static PRETOKEN_RE: OnceLock<Regex> = OnceLock::new();
+    let re = PRETOKEN_RE.get_or_init(|| {
+        Regex::new(r"'s|'t|'re|'ve|'m|'ll|'d|[\p{L}]+|[\p{N}]+|[^\s\p{L}\p{N}]+|\s+")
+            .expect("pretokenizer regex is valid")
+    });
+    re.find_iter(text).map(|m| m.as_str())
+}
+
+/// A small, hand-picked table of common English/code adjacent byte pairs, ranked by
+/// position (lower rank merges first). Not the real cl100k_base/o200k_base
+/// merge-rank table — see the caveat on [`BpeTokenCounter`].
+fn builtin_merge_ranks() -> HashMap<(u8, u8), u32> {
+    const COMMON_PAIRS: &[&str] = &[
+        "th", "he", "in", "er", "an", "re", "on", "at", "en", "nd", "ti", "es", "or",
+        "te", "of", "ed", "is", "it", "al", "ar", "()", "{}", "[]", "//", "/*", "*/",
+        "::", "->", "=>", "==",
+    ];
+
+    COMMON_PAIRS
+        .iter()
+        .enumerate()
+        .map(|(rank, pair)| {
+            let bytes = pair.as_bytes();
+            ((bytes[0], bytes[1]), rank as u32)
+        })
+        .collect()
+}
+
+/// What [`fit_to_budget`] does with the first file in selection order that doesn't
+/// fit the remaining budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowStrategy {
+    /// Omit the file entirely, same as every other file that doesn't fit
+    /// (the original behavior).
+    #[default]
+    Skip,
+    /// Keep the file, truncating its content to the remaining budget and appending a
+    /// `[...truncated N tokens...]` marker.
+    Truncate,
+}
+
+/// A file that did not fit within the token budget.
+#[derive(Debug, Clone)]
+pub struct OmittedFile {
+    /// Path of the omitted file, relative to the scanned directory.
+    pub path: String,
+    /// Estimated token count the file would have added.
+    pub tokens: usize,
+}
+
+/// Result of fitting a set of [`ScannedFile`]s into a token budget.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    /// Files that fit within the budget, in their original scan order.
+    pub included: Vec<ScannedFile>,
+    /// Files dropped to stay within the budget, in their original scan order.
+    pub omitted: Vec<OmittedFile>,
+    /// Sum of estimated token counts across `included`.
+    pub total_tokens: usize,
+}
+
+/// Glob patterns for files evicted before any others, regardless of size, when
+/// trimming to a token budget. Lockfiles and generated test fixtures carry little
+/// value per token once a prompt has to be trimmed, so they make way for source files
+/// first. Matched against each file's basename rather than its full relative path, so
+/// e.g. `go.sum` matches `vendor/mod/go.sum` too, not just one sitting at the scan root.
+const DEFAULT_LOW_PRIORITY_GLOBS: &[&str] =
+    &["go.sum", "*.lock", "package-lock.json", "*.test", "*_test.go", "*.snap"];
+
+/// Greedily selects files to fit within `max_tokens`.
+///
+/// Files are scored for selection order in three tiers: `priority_globs` matches are
+/// kept first, [`DEFAULT_LOW_PRIORITY_GLOBS`] matches (lockfiles, test fixtures) are
+/// evicted first, and everything else sits in between ordered by ascending token
+/// count, so small files fill out the remaining budget before large ones push it over.
+/// The returned [`Budget::included`] preserves the original scan order rather than the
+/// selection order, so rendering a budget looks the same as rendering the full file
+/// list minus the omissions.
+///
+/// # Arguments
+///
+/// * `files` - Files surviving the filter/scanner pipeline
+/// * `max_tokens` - Token budget to fit within
+/// * `priority_globs` - Glob patterns identifying files to keep first
+/// * `counter` - Token counter to estimate each file's cost
+/// * `strategy` - What to do with the first file that doesn't fit (see
+///   [`OverflowStrategy`])
+pub fn fit_to_budget(
+    files: Vec<ScannedFile>,
+    max_tokens: usize,
+    priority_globs: &[String],
+    counter: &dyn TokenCounter,
+    strategy: OverflowStrategy,
+) -> Budget {
+    let priority_patterns: Vec<Pattern> = priority_globs
+        .iter()
+        .filter_map(|pattern| Pattern::new(pattern).ok())
+        .collect();
+
+    let low_priority_patterns: Vec<Pattern> = DEFAULT_LOW_PRIORITY_GLOBS
+        .iter()
+        .filter_map(|pattern| Pattern::new(pattern).ok())
+        .collect();
+
+    let scored: Vec<usize> = files.iter().map(|file| counter.count(&file.content)).collect();
+
+    let mut selection_order: Vec<usize> = (0..files.len()).collect();
+    selection_order.sort_by_key(|&i| {
+        let is_priority = priority_patterns
+            .iter()
+            .any(|pattern| pattern.matches(&files[i].path));
+        let basename = Path::new(&files[i].path)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_else(|| files[i].path.as_str().into());
+        let is_low_priority = !is_priority
+            && low_priority_patterns
+                .iter()
+                .any(|pattern| pattern.matches(&basename));
+        (!is_priority, is_low_priority, scored[i])
+    });
+
+    let mut keep = vec![false; files.len()];
+    let mut truncated: Vec<Option<String>> = vec![None; files.len()];
+    let mut total_tokens = 0;
+    let mut truncated_once = false;
+
+    for i in selection_order {
+        let tokens = scored[i];
+        let remaining = max_tokens.saturating_sub(total_tokens);
+
+        if tokens <= remaining {
+            total_tokens += tokens;
+            keep[i] = true;
+            continue;
+        }
+
+        if strategy == OverflowStrategy::Truncate && !truncated_once && remaining > 0 {
+            let content = truncate_to_tokens(&files[i].content, counter, remaining);
+            total_tokens += counter.count(&content);
+            truncated[i] = Some(content);
+            keep[i] = true;
+            truncated_once = true;
+        }
+    }
+
+    let mut included = Vec::new();
+    let mut omitted = Vec::new();
+
+    for (i, mut file) in files.into_iter().enumerate() {
+        if keep[i] {
+            if let Some(content) = truncated[i].take() {
+                file.content = content;
+            }
+            included.push(file);
+        } else {
+            omitted.push(OmittedFile {
+                path: file.path,
+                tokens: scored[i],
+            });
+        }
+    }
+
+    Budget {
+        included,
+        omitted,
+        total_tokens,
+    }
+}
+
+/// Truncates `content` to the longest character prefix whose estimated token count
+/// (per `counter`) is no more than `target_tokens`, then appends a
+/// `[...truncated N tokens...]` marker reporting how many tokens were cut.
+fn truncate_to_tokens(content: &str, counter: &dyn TokenCounter, target_tokens: usize) -> String {
+    let total_tokens = counter.count(content);
+    if total_tokens <= target_tokens {
+        return content.to_string();
+    }
+
+    let chars: Vec<char> = content.chars().collect();
+    let mut lo = 0usize;
+    let mut hi = chars.len();
+
+    while lo < hi {
+        let mid = lo + (hi - lo + 1) / 2;
+        let prefix: String = chars[..mid].iter().collect();
+        if counter.count(&prefix) <= target_tokens {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    let prefix: String = chars[..lo].iter().collect();
+    let omitted_tokens = total_tokens.saturating_sub(counter.count(&prefix));
+
+    format!("{prefix}\n[...truncated {omitted_tokens} tokens...]\n")
+}
+
+/// Writes a trailing summary reporting the running token total and which files, if
+/// any, were omitted to stay within `max_tokens`.
+pub fn write_summary<W: Write>(
+    budget: &Budget,
+    max_tokens: usize,
+    writer: &mut W,
+) -> anyhow::Result<()> {
+    writeln!(writer, "--------------------")?;
+    writeln!(
+        writer,
+        "Token budget: ~{} / {} tokens used",
+        budget.total_tokens, max_tokens
+    )?;
+
+    if budget.omitted.is_empty() {
+        writeln!(writer, "All scanned files fit within the budget.")?;
+    } else {
+        writeln!(
+            writer,
+            "Omitted {} file(s) to stay within the budget:",
+            budget.omitted.len()
+        )?;
+        for file in &budget.omitted {
+            writeln!(writer, "  {} (~{} tokens)", file.path, file.tokens)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bpe_merges_known_pair() {
+        let counter = BpeTokenCounter::new();
+        // "th" is the lowest-rank pair in builtin_merge_ranks, so it merges into one token.
+        assert_eq!(counter.count("th"), 1);
+        // "xy" has no entry in builtin_merge_ranks, so both bytes stay separate tokens.
+        assert_eq!(counter.count("xy"), 2);
+    }
+
+    #[test]
+    fn test_bpe_merges_operator_pair() {
+        let counter = BpeTokenCounter::new();
+        assert_eq!(counter.count("->"), 1);
+    }
+
+    struct FixedTokenCounter;
+
+    impl TokenCounter for FixedTokenCounter {
+        fn count(&self, text: &str) -> usize {
+            text.len()
+        }
+    }
+
+    #[test]
+    fn test_low_priority_glob_matches_nested_go_sum() {
+        // A real go.sum is almost never at the scan root, so the default low-priority
+        // globs must match on basename rather than requiring a root-relative literal
+        // match (glob::Pattern requires a full-string match for patterns without a
+        // wildcard).
+        let files = vec![
+            ScannedFile {
+                path: "vendor/mod/go.sum".to_string(),
+                content: "a".repeat(100),
+            },
+            ScannedFile {
+                path: "src/main.go".to_string(),
+                content: "b".repeat(100),
+            },
+        ];
+
+        let budget = fit_to_budget(
+            files,
+            100,
+            &[],
+            &FixedTokenCounter,
+            OverflowStrategy::Skip,
+        );
+
+        assert_eq!(budget.included.len(), 1);
+        assert_eq!(budget.included[0].path, "src/main.go");
+        assert_eq!(budget.omitted.len(), 1);
+        assert_eq!(budget.omitted[0].path, "vendor/mod/go.sum");
+    }
+
+    #[test]
+    fn test_bpe_empty_string() {
+        assert_eq!(BpeTokenCounter::new().count(""), 0);
+    }
+
+    #[test]
+    fn test_bpe_splits_on_whitespace_chunks() {
+        // "th is" pretokenizes into ["th", " ", "is"], each chunk merged independently,
+        // so the space never merges with either word.
+        let counter = BpeTokenCounter::new();
+        assert_eq!(counter.count("th is"), 3);
+    }
+}